@@ -0,0 +1,100 @@
+use crate::constants as C;
+use crate::{reactions as R, GasMixture};
+
+/// A portable canister, optionally holding a tank it releases into instead of the environment
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Canister {
+    pub air: GasMixture,
+    /// Pressure above which the canister ruptures
+    pub maximum_pressure: f64,
+    /// Pressure the valve releases gas up to
+    pub release_pressure: f64,
+    pub valve_open: bool,
+    pub holding: Option<GasMixture>,
+    pub ruptured: bool,
+}
+
+impl Canister {
+    /// A canister filled with the gases of `air`, in the volume of a canister
+    pub fn new(air: GasMixture) -> Self {
+        Self {
+            air: GasMixture {
+                volume: C::CANISTER_VOLUME,
+                ..air
+            },
+            maximum_pressure: C::CANISTER_MAXIMUM_PRESSURE,
+            release_pressure: C::ONE_ATMOSPHERE,
+            valve_open: false,
+            holding: None,
+            ruptured: false,
+        }
+    }
+
+    pub fn set_release_pressure(&mut self, pressure: f64) {
+        self.release_pressure = pressure.clamp(
+            C::CANISTER_MIN_RELEASE_PRESSURE,
+            C::CANISTER_MAX_RELEASE_PRESSURE,
+        );
+    }
+
+    /// Put `tank` into the holding slot, returning the tank that was there before
+    pub fn insert_tank(&mut self, tank: GasMixture) -> Option<GasMixture> {
+        self.holding.replace(tank)
+    }
+
+    pub fn eject_tank(&mut self) -> Option<GasMixture> {
+        self.holding.take()
+    }
+
+    /// Connect the canister to a port of `pipenet` for a tick, equalizing their pressures, and return the pipenet afterwards
+    pub fn connect_to(&mut self, pipenet: &GasMixture) -> GasMixture {
+        if self.ruptured {
+            return *pipenet;
+        }
+
+        let (air, pipenet) = self.air.equalize_with(pipenet);
+        self.air = air;
+
+        pipenet
+    }
+
+    /// React the contents once, rupture if they went over the maximum pressure, then release through the open valve.
+    /// Returns `environment` with whatever the canister let out into it.
+    pub fn tick(&mut self, environment: &GasMixture) -> GasMixture {
+        if self.ruptured {
+            return *environment;
+        }
+
+        if self.air.get_heat_cap() > 0. {
+            self.air = R::react_once(self.air);
+        }
+
+        if self.air.get_pressure() > self.maximum_pressure {
+            let (empty, expelled) = self.air.remove_ratio(1.);
+            self.air = empty;
+            self.ruptured = true;
+
+            return environment.assume(&expelled);
+        }
+
+        if !self.valve_open {
+            return *environment;
+        }
+
+        match self.holding {
+            Some(tank) => {
+                let (air, tank) = self.air.release_to(&tank, self.release_pressure);
+                self.air = air;
+                self.holding = Some(tank);
+
+                *environment
+            }
+            None => {
+                let (air, environment) = self.air.release_to(environment, self.release_pressure);
+                self.air = air;
+
+                environment
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Gas, GasMixture};
+
+/// A requirement a gas mixture has to meet for a reaction to happen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Condition {
+    MinTemperature(f64),
+    MaxTemperature(f64),
+    MinPressure(f64),
+    MaxPressure(f64),
+    /// Minimum amount of moles of a gas
+    MinMoles(Gas, f64),
+    /// Minimum share of a gas in the total amount of moles
+    MinRatio(Gas, f64),
+    /// Maximum share of a gas in the total amount of moles
+    MaxRatio(Gas, f64),
+    /// Minimum thermal energy of the mixture
+    MinEnergy(f64),
+}
+
+impl Condition {
+    /// The value of the mixture this condition looks at
+    pub fn current(&self, gm: &GasMixture) -> f64 {
+        match *self {
+            Condition::MinTemperature(_) | Condition::MaxTemperature(_) => gm.temperature,
+            Condition::MinPressure(_) | Condition::MaxPressure(_) => gm.get_pressure(),
+            Condition::MinMoles(gas, _) => gm[gas],
+            Condition::MinRatio(gas, _) | Condition::MaxRatio(gas, _) => {
+                gm[gas] / gm.get_total_amount()
+            }
+            Condition::MinEnergy(_) => gm.get_energy(),
+        }
+    }
+
+    /// The bound the value of the mixture is checked against
+    pub fn required(&self) -> f64 {
+        match *self {
+            Condition::MinTemperature(value)
+            | Condition::MaxTemperature(value)
+            | Condition::MinPressure(value)
+            | Condition::MaxPressure(value)
+            | Condition::MinMoles(_, value)
+            | Condition::MinRatio(_, value)
+            | Condition::MaxRatio(_, value)
+            | Condition::MinEnergy(value) => value,
+        }
+    }
+
+    pub fn is_upper_bound(&self) -> bool {
+        matches!(
+            self,
+            Condition::MaxTemperature(_) | Condition::MaxPressure(_) | Condition::MaxRatio(..)
+        )
+    }
+
+    pub fn holds(&self, gm: &GasMixture) -> bool {
+        if self.is_upper_bound() {
+            self.current(gm) <= self.required()
+        } else {
+            self.current(gm) >= self.required()
+        }
+    }
+}
@@ -10,6 +10,78 @@ pub const T0C: f64 = 273.15;
 pub const T20C: f64 = 293.15;
 /// cbindgen:ignore
 pub const CELL_VOLUME: f64 = 2500.;
+/// cbindgen:ignore
+pub const OPEN_HEAT_TRANSFER_COEFFICIENT: f64 = 0.4;
+/// cbindgen:ignore
+pub const MINIMUM_TEMPERATURE_DELTA_TO_CONSIDER: f64 = 0.5;
+/// cbindgen:ignore
+pub const MOLES_CELLSTANDARD: f64 = ONE_ATMOSPHERE * CELL_VOLUME / (T20C * R_IDEAL_GAS_EQUATION);
+/// cbindgen:ignore
+pub const MINIMUM_AIR_RATIO_TO_SUSPEND: f64 = 0.1;
+/// cbindgen:ignore
+pub const MINIMUM_AIR_RATIO_TO_MOVE: f64 = 0.001;
+/// cbindgen:ignore
+pub const MINIMUM_AIR_TO_SUSPEND: f64 = MOLES_CELLSTANDARD * MINIMUM_AIR_RATIO_TO_SUSPEND;
+/// cbindgen:ignore
+pub const MINIMUM_MOLES_DELTA_TO_MOVE: f64 = MOLES_CELLSTANDARD * MINIMUM_AIR_RATIO_TO_MOVE;
+/// cbindgen:ignore
+pub const MINIMUM_TEMPERATURE_DELTA_TO_SUSPEND: f64 = 4.;
+/// cbindgen:ignore
+pub const EXCITED_GROUP_BREAKDOWN_CYCLES: u32 = 4;
+/// cbindgen:ignore
+pub const EXCITED_GROUP_DISMANTLE_CYCLES: u32 = 16;
+/// cbindgen:ignore
+pub const MONSTERMOS_TURF_LIMIT: usize = 200;
+/// cbindgen:ignore
+pub const MONSTERMOS_HARD_TURF_LIMIT: usize = 2000;
+/// cbindgen:ignore
+pub const OPENTURF_DEFAULT_ATMOS: &str = "o2=22;n2=82;TEMP=293.15";
+/// cbindgen:ignore
+pub const AIRLESS_ATMOS: &str = "TEMP=2.7";
+/// cbindgen:ignore
+pub const LAVALAND_DEFAULT_ATMOS: &str = "o2=14;n2=23;TEMP=300";
+/// cbindgen:ignore
+pub const ICEMOON_DEFAULT_ATMOS: &str = "o2=17;n2=63;TEMP=180";
+/// cbindgen:ignore
+pub const CANISTER_VOLUME: f64 = 1000.;
+/// cbindgen:ignore
+pub const CANISTER_MAXIMUM_PRESSURE: f64 = 90. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const CANISTER_MIN_RELEASE_PRESSURE: f64 = ONE_ATMOSPHERE / 10.;
+/// cbindgen:ignore
+pub const CANISTER_MAX_RELEASE_PRESSURE: f64 = ONE_ATMOSPHERE * 10.;
+/// cbindgen:ignore
+pub const TANK_VOLUME: f64 = 70.;
+/// cbindgen:ignore
+pub const TANK_LEAK_PRESSURE: f64 = 30. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const TANK_RUPTURE_PRESSURE: f64 = 35. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const TANK_FRAGMENT_PRESSURE: f64 = 40. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const TANK_FRAGMENT_SCALE: f64 = 6. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const TANK_MELT_TEMPERATURE: f64 = 1000000.;
+/// cbindgen:ignore
+pub const TANK_MAX_INTEGRITY: u32 = 3;
+/// cbindgen:ignore
+pub const TANK_LEAK_RATIO: f64 = 0.25;
+/// cbindgen:ignore
+pub const MAX_TRANSFER_RATE: f64 = 200.;
+/// cbindgen:ignore
+pub const MAX_OUTPUT_PRESSURE: f64 = 4500.;
+/// cbindgen:ignore
+pub const SCRUBBER_MAXIMUM_PRESSURE: f64 = 50. * ONE_ATMOSPHERE;
+/// cbindgen:ignore
+pub const THERMOMACHINE_HEAT_CAPACITY_SCALE: f64 = 5000.;
+/// cbindgen:ignore
+pub const FREEZER_BASE_MIN_TEMPERATURE: f64 = 170.;
+/// cbindgen:ignore
+pub const HEATER_BASE_MAX_TEMPERATURE: f64 = 140.;
+/// cbindgen:ignore
+pub const THERMOMACHINE_LASER_COOLING: f64 = 15.;
+/// cbindgen:ignore
+pub const TEG_EFFICIENCY: f64 = 0.65;
 
 /// cbindgen:ignore
 pub const FIRE_MINIMUM_TEMPERATURE_TO_SPREAD: f64 = 150.0 + T0C;
@@ -58,6 +130,14 @@ pub const N2O_DECOMPOSITION_ENERGY_RELEASED: f64 = 200000.;
 /// cbindgen:ignore
 pub const NITRYL_FORMATION_ENERGY: f64 = 100000.;
 /// cbindgen:ignore
+pub const PLUOXIUM_FORMATION_MIN_TEMP: f64 = 50.;
+/// cbindgen:ignore
+pub const PLUOXIUM_FORMATION_MAX_TEMP: f64 = T0C;
+/// cbindgen:ignore
+pub const PLUOXIUM_FORMATION_ENERGY: f64 = 250.;
+/// cbindgen:ignore
+pub const PLUOXIUM_MAX_PRODUCTION: f64 = 5.;
+/// cbindgen:ignore
 pub const TRITIUM_BURN_OXY_FACTOR: f64 = 100.;
 /// cbindgen:ignore
 pub const TRITIUM_BURN_TRIT_FACTOR: f64 = 10.;
@@ -66,6 +146,8 @@ pub const TRITIUM_BURN_RADIOACTIVITY_FACTOR: f64 = 50000.;
 /// cbindgen:ignore
 pub const TRITIUM_MINIMUM_RADIATION_ENERGY: f64 = 0.1;
 /// cbindgen:ignore
+pub const TRITIUM_RADIATION_CHANCE: f64 = 10.;
+/// cbindgen:ignore
 pub const MINIMUM_TRIT_OXYBURN_ENERGY: f64 = 2000000.;
 /// cbindgen:ignore
 pub const SUPER_SATURATION_THRESHOLD: f64 = 96.;
@@ -131,3 +213,70 @@ pub const FUSION_RAD_MIDPOINT: f64 = 15.;
 pub const FUSION_MIDDLE_ENERGY_REFERENCE: f64 = 1e6;
 /// cbindgen:ignore
 pub const FUSION_BUFFER_DIVISOR: f64 = 1.;
+
+/// cbindgen:ignore
+pub const PLASMA_HEAT_PENALTY: f64 = 15.;
+/// cbindgen:ignore
+pub const OXYGEN_HEAT_PENALTY: f64 = 1.;
+/// cbindgen:ignore
+pub const PLUOXIUM_HEAT_PENALTY: f64 = -1.;
+/// cbindgen:ignore
+pub const TRITIUM_HEAT_PENALTY: f64 = 10.;
+/// cbindgen:ignore
+pub const CO2_HEAT_PENALTY: f64 = 0.1;
+/// cbindgen:ignore
+pub const NITROGEN_HEAT_PENALTY: f64 = -1.5;
+/// cbindgen:ignore
+pub const BZ_HEAT_PENALTY: f64 = 5.;
+/// cbindgen:ignore
+pub const OXYGEN_TRANSMIT_MODIFIER: f64 = 1.5;
+/// cbindgen:ignore
+pub const PLASMA_TRANSMIT_MODIFIER: f64 = 4.;
+/// cbindgen:ignore
+pub const BZ_TRANSMIT_MODIFIER: f64 = -2.;
+/// cbindgen:ignore
+pub const TRITIUM_TRANSMIT_MODIFIER: f64 = 30.;
+/// cbindgen:ignore
+pub const PLUOXIUM_TRANSMIT_MODIFIER: f64 = -5.;
+/// cbindgen:ignore
+pub const TRITIUM_RADIOACTIVITY_MODIFIER: f64 = 3.;
+/// cbindgen:ignore
+pub const PLUOXIUM_RADIOACTIVITY_MODIFIER: f64 = -2.;
+/// cbindgen:ignore
+pub const BZ_RADIOACTIVITY_MODIFIER: f64 = 5.;
+/// cbindgen:ignore
+pub const N2O_HEAT_RESISTANCE: f64 = 6.;
+/// cbindgen:ignore
+pub const PLUOXIUM_HEAT_RESISTANCE: f64 = 3.;
+/// cbindgen:ignore
+pub const POWERLOSS_INHIBITION_GAS_THRESHOLD: f64 = 0.2;
+/// cbindgen:ignore
+pub const POWERLOSS_INHIBITION_MOLE_THRESHOLD: f64 = 20.;
+/// cbindgen:ignore
+pub const POWERLOSS_INHIBITION_MOLE_BOOST_THRESHOLD: f64 = 500.;
+/// cbindgen:ignore
+pub const MOLE_PENALTY_THRESHOLD: f64 = 1800.;
+/// cbindgen:ignore
+pub const MOLE_HEAT_PENALTY: f64 = 350.;
+/// cbindgen:ignore
+pub const POWER_PENALTY_THRESHOLD: f64 = 5000.;
+/// cbindgen:ignore
+pub const HEAT_PENALTY_THRESHOLD: f64 = 40.;
+/// cbindgen:ignore
+pub const DAMAGE_HARDCAP: f64 = 0.002;
+/// cbindgen:ignore
+pub const DAMAGE_INCREASE_MULTIPLIER: f64 = 0.25;
+/// cbindgen:ignore
+pub const THERMAL_RELEASE_MODIFIER: f64 = 5.;
+/// cbindgen:ignore
+pub const PLASMA_RELEASE_MODIFIER: f64 = 750.;
+/// cbindgen:ignore
+pub const OXYGEN_RELEASE_MODIFIER: f64 = 325.;
+/// cbindgen:ignore
+pub const REACTION_POWER_MODIFIER: f64 = 0.55;
+/// cbindgen:ignore
+pub const MATTER_POWER_CONVERSION: f64 = 10.;
+/// cbindgen:ignore
+pub const SUPERMATTER_GAS_EFFICIENCY: f64 = 0.15;
+/// cbindgen:ignore
+pub const SUPERMATTER_EXPLOSION_POINT: f64 = 900.;
@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::conditions::Condition;
+use crate::reactions::{Reaction, Suppression, REACTIONS};
+use crate::{Gas, GasMixture};
+
+/// A condition of a reaction the mixture doesn't meet
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Unmet {
+    pub condition: Condition,
+    /// Value of the mixture the condition looked at
+    pub current: f64,
+}
+
+/// Why a reaction does or doesn't happen in a mixture
+#[derive(Clone, Debug)]
+pub struct Diagnosis {
+    pub reaction: Reaction,
+    pub unmet: Vec<Unmet>,
+    /// How much of the reaction hyper-noblium lets through, from 0 to 1
+    pub suppression_factor: f64,
+    /// Amount of hyper-noblium in the mixture
+    pub noblium: f64,
+}
+
+impl Diagnosis {
+    pub fn of(reaction: &Reaction, gm: &GasMixture) -> Self {
+        Self {
+            reaction: *reaction,
+            unmet: reaction
+                .conditions
+                .iter()
+                .filter(|condition| !condition.holds(gm))
+                .map(|condition| Unmet {
+                    condition: *condition,
+                    current: condition.current(gm),
+                })
+                .collect(),
+            suppression_factor: reaction.suppression.factor(gm),
+            noblium: gm[Gas::HNb],
+        }
+    }
+
+    pub fn is_suppressed(&self) -> bool {
+        self.suppression_factor < 1.
+    }
+
+    /// Whether the reaction gets to run at all, it may still decide to leave the mixture as it is
+    pub fn can_react(&self) -> bool {
+        self.unmet.is_empty() && self.suppression_factor > 0.
+    }
+}
+
+/// Diagnose every reaction against `gm`, in the order they're attempted in
+pub fn diagnose(gm: &GasMixture) -> Vec<Diagnosis> {
+    REACTIONS
+        .iter()
+        .map(|reaction| Diagnosis::of(reaction, gm))
+        .collect()
+}
+
+/// Human-readable report of `diagnose`, one line per reaction
+pub fn report(gm: &GasMixture) -> String {
+    diagnose(gm)
+        .iter()
+        .map(|diagnosis| diagnosis.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print a number with at most two decimals and without trailing zeroes
+fn short(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_owned()
+    } else {
+        text
+    }
+}
+
+impl fmt::Display for Unmet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (label, current, required, unit) = match self.condition {
+            Condition::MinTemperature(t) | Condition::MaxTemperature(t) => {
+                ("temperature".to_owned(), self.current, t, " K")
+            }
+            Condition::MinPressure(p) | Condition::MaxPressure(p) => {
+                ("pressure".to_owned(), self.current, p, " kPa")
+            }
+            Condition::MinMoles(gas, amount) => {
+                (format!("{:?}", gas), self.current, amount, " mol")
+            }
+            Condition::MinRatio(gas, ratio) | Condition::MaxRatio(gas, ratio) => (
+                format!("{:?} share", gas),
+                self.current * 100.,
+                ratio * 100.,
+                "%",
+            ),
+            Condition::MinEnergy(e) => ("thermal energy".to_owned(), self.current, e, " J"),
+        };
+
+        write!(
+            f,
+            "{} {}/{}{}",
+            label,
+            short(current),
+            short(required),
+            unit
+        )?;
+        if self.condition.is_upper_bound() {
+            write!(f, " at most")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.reaction.id)?;
+
+        if self.unmet.is_empty() && !self.is_suppressed() {
+            return write!(f, "can react");
+        }
+
+        let mut reasons: Vec<String> = self.unmet.iter().map(|unmet| unmet.to_string()).collect();
+        if let Suppression::Full { threshold } | Suppression::Partial { threshold, .. } =
+            self.reaction.suppression
+        {
+            if self.is_suppressed() {
+                reasons.push(format!(
+                    "suppressed by hyper-noblium ({} mol, threshold {} mol)",
+                    short(self.noblium),
+                    short(threshold)
+                ));
+            }
+        }
+
+        write!(f, "{}", reasons.join("; "))
+    }
+}
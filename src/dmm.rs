@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::constants as C;
+use crate::gas_mixture::AtmosStringError;
+use crate::grid::{Grid, Turf};
+
+/// What a tile of a map is as far as gas is concerned
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TileKind {
+    Wall,
+    Floor,
+    Space,
+    Window,
+    Door,
+}
+
+impl TileKind {
+    /// Windows and doors close off their whole tile, since the grid can't block single directions.
+    /// That includes directional windows and doors that start open, rules can mark those as floors to let gas through.
+    pub fn blocks_gas(self) -> bool {
+        matches!(self, TileKind::Wall | TileKind::Window | TileKind::Door)
+    }
+}
+
+/// Things on a tile whose type path is `path` or one of its subtypes are `kind`, the most specific rule wins.
+/// Objects only matter when they block gas, so a more specific rule can let some of them through by marking them as floors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DmmRule {
+    pub path: String,
+    pub kind: TileKind,
+    /// Atmosphere string of matching floors without an `initial_gas_mix` of their own
+    pub atmosphere: Option<String>,
+}
+
+impl DmmRule {
+    pub fn new(path: &str, kind: TileKind) -> Self {
+        Self {
+            path: path.to_owned(),
+            kind,
+            atmosphere: None,
+        }
+    }
+
+    pub fn with_atmosphere(path: &str, atmosphere: &str) -> Self {
+        Self {
+            atmosphere: Some(atmosphere.to_owned()),
+            ..Self::new(path, TileKind::Floor)
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        path.starts_with(&self.path)
+            && (path.len() == self.path.len() || path[self.path.len()..].starts_with('/'))
+    }
+}
+
+/// Rules for the type paths of /tg/station maps
+pub fn default_rules() -> Vec<DmmRule> {
+    vec![
+        DmmRule::new("/turf/closed", TileKind::Wall),
+        DmmRule::new("/turf/open", TileKind::Floor),
+        DmmRule::new("/turf/open/space", TileKind::Space),
+        DmmRule::with_atmosphere("/turf/open/floor/plating/airless", C::AIRLESS_ATMOS),
+        DmmRule::with_atmosphere("/turf/open/floor/engine/airless", C::AIRLESS_ATMOS),
+        DmmRule::new("/obj/structure/window", TileKind::Window),
+        DmmRule::new("/obj/effect/spawner/structure/window", TileKind::Window),
+        DmmRule::new("/obj/machinery/door", TileKind::Door),
+        // Firedoors and preopened blast doors start out open
+        DmmRule::new("/obj/machinery/door/firedoor", TileKind::Floor),
+        DmmRule::new("/obj/machinery/door/poddoor/preopen", TileKind::Floor),
+    ]
+}
+
+/// Why a map couldn't be loaded
+#[derive(Clone, Debug, PartialEq)]
+pub enum DmmError {
+    /// Something unexpected at the given byte offset, or the text ended early
+    Syntax(usize),
+    /// A key used by the map grid that isn't in the dictionary
+    UnknownKey(String),
+    InvalidAtmosphere(AtmosStringError),
+    /// The map has nothing on the requested z level
+    MissingLevel(usize),
+}
+
+impl fmt::Display for DmmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DmmError::Syntax(offset) => write!(f, "syntax error at byte {}", offset),
+            DmmError::UnknownKey(key) => write!(f, "unknown key \"{}\"", key),
+            DmmError::InvalidAtmosphere(error) => write!(f, "invalid atmosphere: {}", error),
+            DmmError::MissingLevel(z) => write!(f, "no z level {}", z),
+        }
+    }
+}
+
+impl std::error::Error for DmmError {}
+
+impl From<AtmosStringError> for DmmError {
+    fn from(error: AtmosStringError) -> Self {
+        DmmError::InvalidAtmosphere(error)
+    }
+}
+
+/// A single level of a map
+#[derive(Clone, Debug, PartialEq)]
+pub struct DmmMap {
+    pub grid: Grid,
+    /// Kind of every tile of the grid, `None` for tiles no rule matched, which are closed
+    pub kinds: Vec<Option<TileKind>>,
+}
+
+/// A type path and the variables it was edited with
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Prefab {
+    path: String,
+    vars: HashMap<String, String>,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(b'/') if self.text[self.pos..].starts_with("//") => {
+                    self.pos = self.text[self.pos..]
+                        .find('\n')
+                        .map_or(self.text.len(), |end| self.pos + end);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), DmmError> {
+        self.skip_whitespace();
+        if self.text[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(DmmError::Syntax(self.pos))
+        }
+    }
+
+    /// Read up to `end`, skipping over strings and brackets
+    fn until(&mut self, end: u8) -> Result<&'a str, DmmError> {
+        let start = self.pos;
+        let end_pos = find_top_level(&self.text[start..], end).ok_or(DmmError::Syntax(start))?;
+        self.pos = start + end_pos + 1;
+
+        Ok(&self.text[start..start + end_pos])
+    }
+
+    fn string(&mut self) -> Result<&'a str, DmmError> {
+        self.expect("\"")?;
+        let start = self.pos;
+        let end = self.text[start..]
+            .find('"')
+            .ok_or(DmmError::Syntax(start))?;
+        self.pos = start + end + 1;
+
+        Ok(&self.text[start..start + end])
+    }
+}
+
+/// Byte offset of the first `end` outside of strings and brackets in `text`
+fn find_top_level(text: &str, end: u8) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            _ if in_string => {}
+            b if b == end && depth == 0 => return Some(i),
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Split `text` on every top level `separator`
+fn split_top_level(text: &str, separators: &[u8]) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+
+    loop {
+        let next = separators
+            .iter()
+            .filter_map(|&separator| find_top_level(rest, separator))
+            .min();
+        match next {
+            Some(i) => {
+                parts.push(rest[..i].to_owned());
+                rest = &rest[i + 1..];
+            }
+            None => {
+                parts.push(rest.to_owned());
+                return parts;
+            }
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_owned(),
+    }
+}
+
+/// Parse the list of prefabs in `text`, which starts at byte `offset` of the map
+fn parse_prefabs(text: &str, offset: usize) -> Result<Vec<Prefab>, DmmError> {
+    split_top_level(text, b",")
+        .iter()
+        .map(|item| {
+            let item = item.trim();
+            let (path, vars) = match item.find('{') {
+                Some(brace) => (
+                    &item[..brace],
+                    item[brace + 1..]
+                        .strip_suffix('}')
+                        .ok_or(DmmError::Syntax(offset))?,
+                ),
+                None => (item, ""),
+            };
+
+            Ok(Prefab {
+                path: path.trim().to_owned(),
+                vars: split_top_level(vars, b";\n")
+                    .iter()
+                    .filter_map(|var| {
+                        let mut parts = var.splitn(2, '=');
+                        let name = parts.next()?.trim();
+                        let value = parts.next()?.trim();
+                        Some((name.to_owned(), unquote(value)))
+                    })
+                    .collect(),
+            })
+        })
+        .filter(|prefab| !matches!(prefab, Ok(prefab) if prefab.path.is_empty()))
+        .collect()
+}
+
+fn most_specific<'r>(rules: &'r [DmmRule], path: &str) -> Option<&'r DmmRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.matches(path))
+        .max_by_key(|rule| rule.path.len())
+}
+
+/// Work out the kind and turf of a tile from everything on it.
+/// Objects that block gas close the tile, otherwise its turf decides.
+fn build_tile(prefabs: &[Prefab], rules: &[DmmRule]) -> Result<(Option<TileKind>, Turf), DmmError> {
+    let blocking = prefabs
+        .iter()
+        .filter_map(|prefab| most_specific(rules, &prefab.path))
+        .map(|rule| rule.kind)
+        .find(|kind| kind.blocks_gas());
+    if let Some(kind) = blocking {
+        return Ok((Some(kind), Turf::Closed));
+    }
+
+    let turf = prefabs
+        .iter()
+        .filter(|prefab| prefab.path.starts_with("/turf/"))
+        .find_map(|prefab| most_specific(rules, &prefab.path).map(|rule| (prefab, rule)));
+
+    match turf {
+        Some((_, rule)) if rule.kind == TileKind::Space => Ok((Some(TileKind::Space), Turf::Space)),
+        Some((prefab, rule)) => {
+            let atmosphere = prefab
+                .vars
+                .get("initial_gas_mix")
+                .map(String::as_str)
+                .or(rule.atmosphere.as_deref())
+                .unwrap_or(C::OPENTURF_DEFAULT_ATMOS);
+            let planetary = prefab
+                .vars
+                .get("planetary_atmos")
+                .is_some_and(|value| value == "1" || value == "TRUE");
+            let turf = if planetary {
+                Turf::planetary(atmosphere)?
+            } else {
+                Turf::open(atmosphere)?
+            };
+
+            Ok((Some(rule.kind), turf))
+        }
+        None => Ok((None, Turf::Closed)),
+    }
+}
+
+/// Load level `z` of a BYOND `.dmm` map, in either the classic or the TGM format.
+/// Tiles are turned into turfs with `rules`, floors get their air from `initial_gas_mix` and default to `OPENTURF_DEFAULT_ATMOS`.
+/// The top left of the map is the top left of the grid.
+pub fn load_dmm(text: &str, rules: &[DmmRule], z: usize) -> Result<DmmMap, DmmError> {
+    let mut parser = Parser { text, pos: 0 };
+    let mut dictionary = HashMap::new();
+    let mut tiles: Vec<((usize, usize), String)> = Vec::new();
+    let mut key_length = 0;
+
+    loop {
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => break,
+            Some(b'"') => {
+                let key = parser.string()?;
+                parser.expect("=")?;
+                parser.expect("(")?;
+                let prefabs_start = parser.pos;
+                let prefabs = parse_prefabs(parser.until(b')')?, prefabs_start)?;
+                key_length = key.len();
+                dictionary.insert(key.to_owned(), prefabs);
+            }
+            Some(b'(') => {
+                parser.pos += 1;
+                let coordinates_start = parser.pos;
+                let coordinates: Vec<usize> = parser
+                    .until(b')')?
+                    .split(',')
+                    .map(|coordinate| coordinate.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| DmmError::Syntax(coordinates_start))?;
+                let (x, y, level) = match coordinates[..] {
+                    [x, y, level] if x > 0 && y > 0 => (x, y, level),
+                    _ => return Err(DmmError::Syntax(coordinates_start)),
+                };
+                parser.expect("=")?;
+                parser.expect("{\"")?;
+                let block_start = parser.pos;
+                let block_end = text[block_start..]
+                    .find("\"}")
+                    .ok_or(DmmError::Syntax(block_start))?;
+                parser.pos = block_start + block_end + 2;
+
+                if level != z || key_length == 0 {
+                    continue;
+                }
+                let rows: Vec<&str> = text[block_start..block_start + block_end]
+                    .lines()
+                    .map(str::trim)
+                    .filter(|row| !row.is_empty())
+                    .collect();
+                for (row_index, row) in rows.iter().enumerate() {
+                    // Keys are made of ASCII letters, so anything else can't be split into them
+                    if !row.is_ascii() || row.len() % key_length != 0 {
+                        return Err(DmmError::Syntax(block_start));
+                    }
+                    for column in 0..row.len() / key_length {
+                        let key = &row[column * key_length..(column + 1) * key_length];
+                        tiles.push(((x + column, y + rows.len() - 1 - row_index), key.to_owned()));
+                    }
+                }
+            }
+            Some(_) => return Err(DmmError::Syntax(parser.pos)),
+        }
+    }
+
+    let width = tiles
+        .iter()
+        .map(|((x, _), _)| *x)
+        .max()
+        .ok_or(DmmError::MissingLevel(z))?;
+    let height = tiles.iter().map(|((_, y), _)| *y).max().unwrap_or(0);
+    let mut grid = Grid::new(width, height, Turf::Closed);
+    let mut kinds = vec![None; width * height];
+
+    for ((x, y), key) in tiles {
+        let prefabs = dictionary
+            .get(&key)
+            .ok_or_else(|| DmmError::UnknownKey(key.clone()))?;
+        let (kind, turf) = build_tile(prefabs, rules)?;
+        let index = grid.index(x - 1, height - y);
+        grid.turfs[index] = turf;
+        kinds[index] = kind;
+    }
+
+    Ok(DmmMap { grid, kinds })
+}
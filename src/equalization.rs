@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::constants as C;
+use crate::grid::{Grid, Turf};
+use crate::GasMixture;
+
+/// Moles of gas moved from a turf into a neighbouring one
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub moles: f64,
+}
+
+/// The result of equalizing a zone of turfs in a single tick, like monstermos does
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Equalization {
+    /// Turfs the equalization covered
+    pub turfs: Vec<usize>,
+    /// Net transfer over every edge gas moved through
+    pub transfers: Vec<Transfer>,
+    /// Whether the zone was open to space and got sucked out
+    pub decompressed: bool,
+}
+
+impl Equalization {
+    pub fn moved_moles(&self) -> f64 {
+        self.transfers.iter().map(|transfer| transfer.moles).sum()
+    }
+}
+
+/// Net flows between pairs of turfs, keyed with the lower index first
+#[derive(Default)]
+struct Flows(HashMap<(usize, usize), f64>);
+
+impl Flows {
+    fn add(&mut self, from: usize, to: usize, moles: f64) {
+        if from < to {
+            *self.0.entry((from, to)).or_insert(0.) += moles;
+        } else {
+            *self.0.entry((to, from)).or_insert(0.) -= moles;
+        }
+    }
+
+    fn into_transfers(self) -> Vec<Transfer> {
+        let mut transfers: Vec<Transfer> = self
+            .0
+            .into_iter()
+            .filter(|(_, moles)| *moles != 0.)
+            .map(|((a, b), moles)| {
+                if moles > 0. {
+                    Transfer {
+                        from: a,
+                        to: b,
+                        moles,
+                    }
+                } else {
+                    Transfer {
+                        from: b,
+                        to: a,
+                        moles: -moles,
+                    }
+                }
+            })
+            .collect();
+        transfers.sort_by_key(|transfer| (transfer.from, transfer.to));
+
+        transfers
+    }
+}
+
+impl Grid {
+    /// Equalize the pressure of the zone of turfs connected to `start` in one go, like monstermos does.
+    /// Zones open to space are sucked out into it instead, pulling everything along the shortest path towards the breach.
+    /// Every covered turf is woken up so regular sharing can take over afterwards.
+    pub fn equalize(&mut self, start: usize) -> Equalization {
+        if !self.turfs[start].is_simulated() {
+            return Equalization::default();
+        }
+
+        let mut zone = vec![start];
+        let mut in_zone = HashMap::new();
+        in_zone.insert(start, 0);
+        let mut space = Vec::new();
+        let mut next = 0;
+
+        while next < zone.len() {
+            for neighbour in self.neighbours(zone[next]) {
+                if self.turfs[neighbour] == Turf::Space {
+                    if !space.contains(&neighbour) {
+                        space.push(neighbour);
+                    }
+                } else if self.turfs[neighbour].is_simulated()
+                    && !in_zone.contains_key(&neighbour)
+                    && zone.len() < C::MONSTERMOS_TURF_LIMIT
+                {
+                    in_zone.insert(neighbour, zone.len());
+                    zone.push(neighbour);
+                }
+            }
+            next += 1;
+        }
+
+        let equalization = if space.is_empty() {
+            self.equalize_zone(zone, &in_zone)
+        } else {
+            self.decompress(space)
+        };
+
+        for &turf in &equalization.turfs {
+            self.activate(turf);
+        }
+
+        equalization
+    }
+
+    /// Equalize every active turf that differs from one of its neighbours, each turf is covered at most once
+    pub fn equalize_active(&mut self) -> Vec<Equalization> {
+        let mut covered: HashSet<usize> = HashSet::new();
+        let mut equalizations = Vec::new();
+        let active: Vec<usize> = self.active.iter().copied().collect();
+
+        for index in active {
+            if covered.contains(&index) || !self.turfs[index].is_simulated() {
+                continue;
+            }
+
+            let air = self.turfs[index].air().unwrap();
+            if !self
+                .neighbours(index)
+                .into_iter()
+                .any(|neighbour| air.differs_from(&self.turfs[neighbour].air().unwrap()))
+            {
+                continue;
+            }
+
+            let equalization = self.equalize(index);
+            covered.extend(&equalization.turfs);
+            equalizations.push(equalization);
+        }
+
+        equalizations
+    }
+
+    /// Direction and amount of gas flowing out of every turf of the grid, which is what pushes things around on it.
+    /// Y grows downwards, like the rows of the grid.
+    pub fn pressure_vectors(&self, transfers: &[Transfer]) -> Vec<(f64, f64)> {
+        let mut vectors = vec![(0., 0.); self.turfs.len()];
+
+        for transfer in transfers {
+            let (from_x, from_y) = (transfer.from % self.width, transfer.from / self.width);
+            let (to_x, to_y) = (transfer.to % self.width, transfer.to / self.width);
+            let vector = &mut vectors[transfer.from];
+            vector.0 += (to_x as f64 - from_x as f64) * transfer.moles;
+            vector.1 += (to_y as f64 - from_y as f64) * transfer.moles;
+        }
+
+        vectors
+    }
+
+    /// Move gas from the turfs with more than the average amount of moles to the ones with less, along the shortest paths
+    fn equalize_zone(&mut self, zone: Vec<usize>, in_zone: &HashMap<usize, usize>) -> Equalization {
+        let moles: Vec<f64> = zone
+            .iter()
+            .map(|&turf| self.turfs[turf].air().unwrap().get_total_amount())
+            .collect();
+        let average = moles.iter().sum::<f64>() / zone.len() as f64;
+        let mut deltas: Vec<f64> = moles.iter().map(|amount| amount - average).collect();
+
+        let givers: Vec<usize> = (0..zone.len()).filter(|&i| deltas[i] > 0.).collect();
+        let takers: Vec<usize> = (0..zone.len()).filter(|&i| deltas[i] < 0.).collect();
+        let mut flows = Flows::default();
+
+        // Search from whichever side has fewer turfs, paths lead back to where the search started
+        let pushing = givers.len() < takers.len();
+        let sources = if pushing { givers } else { takers };
+        for source in sources {
+            let mut parents = vec![None; zone.len()];
+            let mut visited = vec![false; zone.len()];
+            let mut queue = VecDeque::new();
+            visited[source] = true;
+            queue.push_back(source);
+
+            while let Some(current) = queue.pop_front() {
+                if deltas[source] == 0. {
+                    break;
+                }
+
+                if current != source && deltas[current] != 0. && (deltas[current] > 0.) != pushing {
+                    let amount = deltas[source].abs().min(deltas[current].abs());
+                    let signed = if pushing { amount } else { -amount };
+                    deltas[source] -= signed;
+                    deltas[current] += signed;
+
+                    let mut node = current;
+                    while let Some(parent) = parents[node] {
+                        if pushing {
+                            flows.add(zone[parent], zone[node], amount);
+                        } else {
+                            flows.add(zone[node], zone[parent], amount);
+                        }
+                        node = parent;
+                    }
+                }
+
+                for neighbour in self.neighbours(zone[current]) {
+                    if let Some(&i) = in_zone.get(&neighbour) {
+                        if !visited[i] {
+                            visited[i] = true;
+                            parents[i] = Some(current);
+                            queue.push_back(i);
+                        }
+                    }
+                }
+            }
+        }
+
+        let transfers = flows.into_transfers();
+        self.apply_transfers(&transfers);
+
+        Equalization {
+            turfs: zone,
+            transfers,
+            decompressed: false,
+        }
+    }
+
+    /// Move the gas of every turf reachable from `space` into it, each turf pushing everything towards the closest breach
+    fn decompress(&mut self, space: Vec<usize>) -> Equalization {
+        let mut parents = HashMap::new();
+        let mut order = Vec::new();
+        let mut queue: VecDeque<usize> = space.iter().copied().collect();
+
+        while let Some(current) = queue.pop_front() {
+            for neighbour in self.neighbours(current) {
+                if self.turfs[neighbour].is_simulated()
+                    && !parents.contains_key(&neighbour)
+                    && order.len() < C::MONSTERMOS_HARD_TURF_LIMIT
+                {
+                    parents.insert(neighbour, current);
+                    order.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        let mut incoming: HashMap<usize, f64> = HashMap::new();
+        let mut flows = Flows::default();
+        for &turf in order.iter().rev() {
+            let air = self.turfs[turf].air().unwrap();
+            let moles = air.get_total_amount() + incoming.get(&turf).copied().unwrap_or(0.);
+            let parent = parents[&turf];
+            flows.add(turf, parent, moles);
+            *incoming.entry(parent).or_insert(0.) += moles;
+
+            self.turfs[turf].set_air(air.remove_ratio(1.).0);
+        }
+
+        let mut turfs = space;
+        turfs.extend(order);
+
+        Equalization {
+            turfs,
+            transfers: flows.into_transfers(),
+            decompressed: true,
+        }
+    }
+
+    /// Move the gas of `transfers`, turfs only give away gas once everything flowing into them has arrived
+    fn apply_transfers(&mut self, transfers: &[Transfer]) {
+        let mut incoming: HashMap<usize, usize> = HashMap::new();
+        for transfer in transfers {
+            *incoming.entry(transfer.to).or_insert(0) += 1;
+        }
+
+        let mut done = vec![false; transfers.len()];
+        let mut queue: VecDeque<usize> = transfers
+            .iter()
+            .map(|transfer| transfer.from)
+            .filter(|turf| !incoming.contains_key(turf))
+            .collect();
+
+        loop {
+            while let Some(turf) = queue.pop_front() {
+                for (i, transfer) in transfers.iter().enumerate() {
+                    if done[i] || transfer.from != turf {
+                        continue;
+                    }
+
+                    done[i] = true;
+                    self.move_gas(transfer);
+                    let remaining = incoming.get_mut(&transfer.to).unwrap();
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        queue.push_back(transfer.to);
+                    }
+                }
+            }
+
+            // Whatever's left goes around in circles, so there's no right order for it
+            match done.iter().position(|done| !done) {
+                Some(i) => queue.push_back(transfers[i].from),
+                None => break,
+            }
+        }
+    }
+
+    fn move_gas(&mut self, transfer: &Transfer) {
+        let from: GasMixture = self.turfs[transfer.from].air().unwrap();
+        let to: GasMixture = self.turfs[transfer.to].air().unwrap();
+        let (rest, removed) = from.remove(transfer.moles);
+
+        self.turfs[transfer.from].set_air(rest);
+        self.turfs[transfer.to].set_air(to.assume(&removed));
+    }
+}
@@ -1,8 +1,20 @@
 extern crate libc;
 
-use std::{convert::TryInto, slice::ChunksExactMut};
+use libc::c_char;
+use std::{
+    convert::{TryFrom, TryInto},
+    slice::ChunksExactMut,
+};
 
-use crate::{gas::GasEnumMap, reactions as R, Gas, GasVec};
+use crate::dmm as M;
+use crate::equalization::Transfer;
+use crate::grid::{Grid, TickStats, Turf};
+use crate::simulation::{Simulation, Stop, StopCondition};
+use crate::snapshot::Snapshot;
+use crate::tank::{self as T, TankFailure, TransferValveResult};
+use crate::{
+    diagnostics as D, gas::GasEnumMap, reactions as R, reactions::ReactionOutput, Gas, GasVec,
+};
 use crate::{gas::GAS_AMT, GasMixture};
 
 #[derive(Clone, Copy)]
@@ -73,6 +85,108 @@ impl GasMixtureManifoldFFI {
     }
 }
 
+/// Maximum length of the texts in `ReactionInfoFFI`, including the terminating null
+pub const REACTION_TEXT_LEN: usize = 64;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct ReactionInfoFFI {
+    id: [c_char; REACTION_TEXT_LEN],
+    name: [c_char; REACTION_TEXT_LEN],
+    min_temperature: f64,
+    max_temperature: f64,
+    required_gases: [f64; GAS_AMT],
+    products: [bool; GAS_AMT],
+}
+
+fn to_c_text(text: &str) -> [c_char; REACTION_TEXT_LEN] {
+    let mut result = [0; REACTION_TEXT_LEN];
+    result
+        .iter_mut()
+        .zip(text.bytes().take(REACTION_TEXT_LEN - 1))
+        .for_each(|(c, b)| *c = b as c_char);
+
+    result
+}
+
+impl From<&R::Reaction> for ReactionInfoFFI {
+    fn from(reaction: &R::Reaction) -> Self {
+        let mut required_gases = [0.; GAS_AMT];
+        reaction
+            .required_gases()
+            .for_each(|(gas, amount)| required_gases[gas as usize] = amount);
+
+        let mut products = [false; GAS_AMT];
+        reaction
+            .products
+            .iter()
+            .for_each(|gas| products[*gas as usize] = true);
+
+        ReactionInfoFFI {
+            id: to_c_text(reaction.id),
+            name: to_c_text(reaction.name),
+            min_temperature: reaction.min_temperature(),
+            max_temperature: reaction.max_temperature(),
+            required_gases,
+            products,
+        }
+    }
+}
+
+/// Amount of gases in the arrays of `GasMixtureFFI` and `ReactionInfoFFI`, in the order of `Gas`.
+/// Hosts should check it against their own layout, it went from 12 to 13 when miasma was added.
+#[no_mangle]
+pub extern "C" fn gas_count() -> usize {
+    GAS_AMT
+}
+
+/// Amount of reactions that can be queried with `reaction_info`
+#[no_mangle]
+pub extern "C" fn reaction_count() -> usize {
+    R::REACTIONS.len()
+}
+
+/// Write the description of the reaction at `index` into `out_info`, in the order the reactions are attempted in.
+/// Returns false without writing anything if there's no reaction at `index`.
+///
+/// # Safety
+/// `out_info` has to point to a writable `ReactionInfoFFI`.
+#[no_mangle]
+pub unsafe extern "C" fn reaction_info(index: usize, out_info: *mut ReactionInfoFFI) -> bool {
+    match R::REACTIONS.get(index) {
+        Some(reaction) => {
+            *out_info = reaction.into();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Write a human-readable report on why each reaction does or doesn't happen in `in_gas_mix` into `out_text`, one line per reaction.
+/// At most `out_len` bytes are written, including the terminating null. Returns the length of the full report, excluding the null.
+///
+/// # Safety
+/// `in_gas_mix` has to point to a valid mixture, and `out_text` to `out_len` writable bytes unless `out_len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn diagnose(
+    in_gas_mix: *const GasMixtureFFI,
+    out_text: *mut c_char,
+    out_len: usize,
+) -> usize {
+    let report = D::report(&(*in_gas_mix).into());
+
+    if out_len > 0 {
+        let text = std::slice::from_raw_parts_mut(out_text, out_len);
+        let written = report.len().min(out_len - 1);
+        text.iter_mut()
+            .zip(report.bytes().take(written))
+            .for_each(|(c, b)| *c = b as c_char);
+        text[written] = 0;
+    }
+
+    report.len()
+}
+
 /// Take a gas mixture from `in_gas_mix`, react it a single time and write the result into `out_gas_mix`
 #[no_mangle]
 pub unsafe extern "C" fn react_once(
@@ -82,6 +196,22 @@ pub unsafe extern "C" fn react_once(
     *out_gas_mix = R::react_once((*in_gas_mix).into()).into();
 }
 
+/// Take a gas mixture from `in_gas_mix`, react it a single time, write the result into `out_gas_mix` and the side effects of the reactions into `out_output`.
+/// The side effects include research points and the radiation emitted by tritium fires and fusion.
+///
+/// # Safety
+/// `in_gas_mix` has to point to a valid mixture, `out_gas_mix` and `out_output` have to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn react_once_with_output(
+    in_gas_mix: *const GasMixtureFFI,
+    out_gas_mix: *mut GasMixtureFFI,
+    out_output: *mut ReactionOutput,
+) {
+    let (result, output) = R::react_once_with_output((*in_gas_mix).into());
+    *out_gas_mix = result.into();
+    *out_output = output;
+}
+
 /// Take a gas mixture from `in_gas_mix`, react it until it stops and write the result into `out_gas_mix`
 #[no_mangle]
 pub unsafe extern "C" fn react_until_done(
@@ -182,3 +312,555 @@ pub unsafe extern "C" fn merge_all(
         .fold(GasMixture::zero(), |lhs, rhs| lhs + *rhs)
         .into()
 }
+
+/// Stop once the pressure rises above `StopConditionsFFI.pressure_above`
+pub const STOP_PRESSURE_ABOVE: u32 = 1;
+/// Stop once the temperature rises above `StopConditionsFFI.temperature_above`
+pub const STOP_TEMPERATURE_ABOVE: u32 = 1 << 1;
+/// Stop once the temperature drops below `StopConditionsFFI.temperature_below`
+pub const STOP_TEMPERATURE_BELOW: u32 = 1 << 2;
+/// Stop once `StopConditionsFFI.depleted_gas` runs out
+pub const STOP_GAS_DEPLETED: u32 = 1 << 3;
+/// Stop once a step changes nothing by more than `StopConditionsFFI.converged_tolerance`
+pub const STOP_CONVERGED: u32 = 1 << 4;
+/// Reported instead of a stop flag when `StopConditionsFFI.depleted_gas` isn't the index of a gas, nothing is run then
+pub const STOP_INVALID_CONDITIONS: u32 = 1 << 31;
+
+/// Conditions that end a run early, only the ones enabled in `flags` are checked
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct StopConditionsFFI {
+    flags: u32,
+    pressure_above: f64,
+    temperature_above: f64,
+    temperature_below: f64,
+    /// Index of the gas in the order of `Gas`
+    depleted_gas: u32,
+    converged_tolerance: f64,
+}
+
+impl TryFrom<StopConditionsFFI> for Vec<StopCondition> {
+    type Error = u32;
+
+    /// Fails with the index of `depleted_gas` if it isn't a gas, even when its flag isn't set
+    fn try_from(source: StopConditionsFFI) -> Result<Self, u32> {
+        let depleted_gas =
+            Gas::from_index(source.depleted_gas as usize).ok_or(source.depleted_gas)?;
+
+        Ok([
+            (
+                STOP_PRESSURE_ABOVE,
+                StopCondition::PressureAbove(source.pressure_above),
+            ),
+            (
+                STOP_TEMPERATURE_ABOVE,
+                StopCondition::TemperatureAbove(source.temperature_above),
+            ),
+            (
+                STOP_TEMPERATURE_BELOW,
+                StopCondition::TemperatureBelow(source.temperature_below),
+            ),
+            (STOP_GAS_DEPLETED, StopCondition::GasDepleted(depleted_gas)),
+            (
+                STOP_CONVERGED,
+                StopCondition::Converged(source.converged_tolerance),
+            ),
+        ]
+        .iter()
+        .filter(|(flag, _)| source.flags & flag != 0)
+        .map(|(_, condition)| *condition)
+        .collect())
+    }
+}
+
+fn stop_flag(condition: StopCondition) -> u32 {
+    match condition {
+        StopCondition::PressureAbove(_) => STOP_PRESSURE_ABOVE,
+        StopCondition::TemperatureAbove(_) => STOP_TEMPERATURE_ABOVE,
+        StopCondition::TemperatureBelow(_) => STOP_TEMPERATURE_BELOW,
+        StopCondition::GasDepleted(_) => STOP_GAS_DEPLETED,
+        StopCondition::Converged(_) => STOP_CONVERGED,
+    }
+}
+
+/// Which condition ended a run and on which step, `flag` is 0 if the run went on for all of its steps
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct StopFFI {
+    flag: u32,
+    step: usize,
+}
+
+impl From<Option<Stop>> for StopFFI {
+    fn from(source: Option<Stop>) -> Self {
+        match source {
+            Some(stop) => StopFFI {
+                flag: stop_flag(stop.condition),
+                step: stop.step,
+            },
+            None => StopFFI { flag: 0, step: 0 },
+        }
+    }
+}
+
+/// Take a gas mixture from `in_gas_mix`, react it up to `out_gas_mix.len` times and write the intermediate states into `out_gas_mix`, stopping right after any of the `stop_conditions` holds.
+/// What stopped the run is written into `out_stop`. Returns the amount of states written.
+/// If `depleted_gas` isn't the index of a gas nothing is run, `out_stop` gets `STOP_INVALID_CONDITIONS` and 0 is returned.
+///
+/// # Safety
+/// `in_gas_mix` has to point to a valid mixture and `stop_conditions` to valid conditions.
+/// `out_gas_mix` has to point to an array of `len` writable mixtures and `out_stop` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn react_several_until(
+    in_gas_mix: *const GasMixtureFFI,
+    out_gas_mix: *const GasMixtureArrayFFI,
+    stop_conditions: *const StopConditionsFFI,
+    out_stop: *mut StopFFI,
+) -> usize {
+    let conditions: Vec<StopCondition> = match (*stop_conditions).try_into() {
+        Ok(conditions) => conditions,
+        Err(_) => {
+            *out_stop = StopFFI {
+                flag: STOP_INVALID_CONDITIONS,
+                step: 0,
+            };
+            return 0;
+        }
+    };
+    let (timeline, stop) =
+        R::react_several_until((*in_gas_mix).into(), (*out_gas_mix).len, &conditions);
+
+    (*out_gas_mix)
+        .as_slice_mut()
+        .iter_mut()
+        .zip(timeline.iter())
+        .for_each(|(out, gm)| *out = (*gm).into());
+    *out_stop = stop.into();
+
+    timeline.len()
+}
+
+/// Start a simulation of the gas mixture in `in_gas_mix`. The returned handle has to be freed with `simulation_destroy`.
+///
+/// # Safety
+/// `in_gas_mix` has to point to a valid mixture.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_create(in_gas_mix: *const GasMixtureFFI) -> *mut Simulation {
+    Box::into_raw(Box::new(Simulation::new((*in_gas_mix).into())))
+}
+
+/// Start a simulation of the gas mixture in `in_gas_mix` that rolls the chances of its reactions with a generator seeded with `seed`.
+/// The returned handle has to be freed with `simulation_destroy`.
+///
+/// # Safety
+/// `in_gas_mix` has to point to a valid mixture.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_create_seeded(
+    in_gas_mix: *const GasMixtureFFI,
+    seed: u64,
+) -> *mut Simulation {
+    Box::into_raw(Box::new(Simulation::seeded((*in_gas_mix).into(), seed)))
+}
+
+/// React the mixture of `simulation` `times` times
+///
+/// # Safety
+/// `simulation` has to be a live handle from `simulation_create`, `simulation_create_seeded` or `simulation_restore`.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_step(simulation: *mut Simulation, times: usize) {
+    for _ in 1..=times {
+        (*simulation).step();
+    }
+}
+
+/// React the mixture of `simulation` up to `times` times, stopping right after any of the `stop_conditions` holds.
+/// Returns the flag of the condition that stopped the run, or 0 if none did.
+/// Returns `STOP_INVALID_CONDITIONS` without stepping if `depleted_gas` isn't the index of a gas.
+///
+/// # Safety
+/// `simulation` has to be a live handle from `simulation_create`, `simulation_create_seeded` or `simulation_restore`, and `stop_conditions` has to point to valid conditions.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_run(
+    simulation: *mut Simulation,
+    times: usize,
+    stop_conditions: *const StopConditionsFFI,
+) -> u32 {
+    let conditions: Vec<StopCondition> = match (*stop_conditions).try_into() {
+        Ok(conditions) => conditions,
+        Err(_) => return STOP_INVALID_CONDITIONS,
+    };
+    (*simulation)
+        .run(times, &conditions)
+        .map_or(0, |stop| stop_flag(stop.condition))
+}
+
+/// Write the current mixture of `simulation` into `out_gas_mix`, the side effects of its latest step into `out_output`, and return the amount of steps taken so far
+///
+/// # Safety
+/// `simulation` has to be a live handle from `simulation_create`, `simulation_create_seeded` or `simulation_restore`, `out_gas_mix` and `out_output` have to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_read(
+    simulation: *const Simulation,
+    out_gas_mix: *mut GasMixtureFFI,
+    out_output: *mut ReactionOutput,
+) -> usize {
+    *out_gas_mix = (*simulation).mixture.into();
+    *out_output = (*simulation).output;
+    (*simulation).steps
+}
+
+/// Free a simulation created by `simulation_create`
+///
+/// # Safety
+/// `simulation` has to be null or a live handle from `simulation_create`, `simulation_create_seeded` or `simulation_restore`, it can't be used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_destroy(simulation: *mut Simulation) {
+    if !simulation.is_null() {
+        drop(Box::from_raw(simulation));
+    }
+}
+
+/// The tank transfer valve bomb didn't fail in the given amount of steps
+pub const TTV_INTACT: u32 = 0;
+/// The tank ruptured, releasing its gas without exploding
+pub const TTV_RUPTURED: u32 = 1;
+/// The tank exploded, the ranges of `TransferValveResultFFI` are set
+pub const TTV_EXPLODED: u32 = 2;
+
+/// How a tank transfer valve bomb went, the explosion ranges are 0 unless `failure` is `TTV_EXPLODED`
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct TransferValveResultFFI {
+    failure: u32,
+    step: usize,
+    range: f64,
+    devastation: i32,
+    heavy: i32,
+    light: i32,
+    flash: i32,
+}
+
+impl From<&TransferValveResult> for TransferValveResultFFI {
+    fn from(source: &TransferValveResult) -> Self {
+        let empty = TransferValveResultFFI {
+            failure: TTV_INTACT,
+            step: source.failed_at.unwrap_or(0),
+            range: 0.,
+            devastation: 0,
+            heavy: 0,
+            light: 0,
+            flash: 0,
+        };
+
+        match source.failure {
+            None => empty,
+            Some(TankFailure::Rupture) => TransferValveResultFFI {
+                failure: TTV_RUPTURED,
+                ..empty
+            },
+            Some(TankFailure::Explosion(explosion)) => TransferValveResultFFI {
+                failure: TTV_EXPLODED,
+                range: explosion.range,
+                devastation: explosion.devastation,
+                heavy: explosion.heavy,
+                light: explosion.light,
+                flash: explosion.flash,
+                ..empty
+            },
+        }
+    }
+}
+
+/// Merge the tanks in `tank_one` and `tank_two` like a tank transfer valve does and react the result up to `out_gas_mix.len` times or until the tank fails.
+/// The state of the tank after every step is written into `out_gas_mix` and how it failed into `out_result`. Returns the amount of states written.
+///
+/// # Safety
+/// `tank_one` and `tank_two` have to point to valid mixtures, `out_gas_mix` to an array of `len` writable mixtures and `out_result` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn transfer_valve(
+    tank_one: *const GasMixtureFFI,
+    tank_two: *const GasMixtureFFI,
+    out_gas_mix: *const GasMixtureArrayFFI,
+    out_result: *mut TransferValveResultFFI,
+) -> usize {
+    let result = T::transfer_valve(&(*tank_one).into(), &(*tank_two).into(), (*out_gas_mix).len);
+
+    (*out_gas_mix)
+        .as_slice_mut()
+        .iter_mut()
+        .zip(result.timeline.steps.iter())
+        .for_each(|(out, step)| *out = step.mixture.into());
+    *out_result = (&result).into();
+
+    result.timeline.steps.len()
+}
+
+/// A wall, or any other turf that blocks gas
+pub const TURF_CLOSED: u8 = 0;
+/// A regular open turf
+pub const TURF_OPEN: u8 = 1;
+/// A space turf, it always holds an empty mixture at the temperature of space
+pub const TURF_SPACE: u8 = 2;
+/// An open turf that slowly returns to `atmosphere`
+pub const TURF_PLANETARY: u8 = 3;
+/// An open turf whose mixture never changes
+pub const TURF_IMMUTABLE: u8 = 4;
+
+/// A turf of a grid, `air` is ignored for closed and space turfs and `atmosphere` is only used by planetary turfs
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct TurfFFI {
+    kind: u8,
+    air: GasMixtureFFI,
+    atmosphere: GasMixtureFFI,
+}
+
+impl From<TurfFFI> for Turf {
+    fn from(source: TurfFFI) -> Self {
+        match source.kind {
+            TURF_OPEN => Turf::Open(source.air.into()),
+            TURF_SPACE => Turf::Space,
+            TURF_PLANETARY => Turf::Planetary {
+                air: source.air.into(),
+                atmosphere: source.atmosphere.into(),
+            },
+            TURF_IMMUTABLE => Turf::Immutable(source.air.into()),
+            _ => Turf::Closed,
+        }
+    }
+}
+
+/// Create a grid of `width` by `height` turfs from `turfs`, row by row starting at the top left.
+/// The returned handle has to be freed with `grid_destroy`.
+///
+/// # Safety
+/// `turfs` has to point to `width * height` valid turfs.
+#[no_mangle]
+pub unsafe extern "C" fn grid_create(
+    width: usize,
+    height: usize,
+    turfs: *const TurfFFI,
+) -> *mut Grid {
+    let mut grid = Grid::new(width, height, Turf::Closed);
+    grid.turfs = std::slice::from_raw_parts(turfs, width * height)
+        .iter()
+        .map(|&turf| turf.into())
+        .collect();
+
+    Box::into_raw(Box::new(grid))
+}
+
+/// Load level `z` of the `.dmm` map in the null terminated `text` into a grid, with the rules for /tg/station maps.
+/// Returns a null pointer if the map couldn't be loaded, otherwise the handle has to be freed with `grid_destroy`.
+///
+/// # Safety
+/// `text` has to point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn grid_load_dmm(text: *const c_char, z: usize) -> *mut Grid {
+    let text = std::ffi::CStr::from_ptr(text).to_string_lossy();
+
+    match M::load_dmm(&text, &M::default_rules(), z) {
+        Ok(map) => Box::into_raw(Box::new(map.grid)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Tick `grid` once, writing what happened into `out_stats`
+///
+/// # Safety
+/// `grid` has to be a live handle from `grid_create`, `grid_load_dmm` or `grid_restore`, and `out_stats` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn grid_tick(grid: *mut Grid, out_stats: *mut TickStats) {
+    *out_stats = (*grid).tick();
+}
+
+fn equalize(grid: &mut Grid, out_vectors: &mut [f64]) -> Vec<Transfer> {
+    let transfers: Vec<_> = grid
+        .equalize_active()
+        .iter()
+        .flat_map(|equalization| equalization.transfers.iter().copied())
+        .collect();
+
+    out_vectors
+        .chunks_exact_mut(2)
+        .zip(grid.pressure_vectors(&transfers))
+        .for_each(|(out, (x, y))| {
+            out[0] = x;
+            out[1] = y;
+        });
+
+    transfers
+}
+
+/// Equalize every active zone of `grid` that isn't settled yet.
+/// The flow out of every turf is written into `out_vectors` as x and y pairs, so it has to hold twice as many values as the grid has turfs.
+/// Returns the amount of moles moved between turfs.
+///
+/// # Safety
+/// `grid` has to be a live handle from `grid_create`, `grid_load_dmm` or `grid_restore`, and `out_vectors` has to point to twice as many writable values as the grid has turfs.
+#[no_mangle]
+pub unsafe extern "C" fn grid_equalize(grid: *mut Grid, out_vectors: *mut f64) -> f64 {
+    let out_vectors = std::slice::from_raw_parts_mut(out_vectors, (*grid).turfs.len() * 2);
+
+    equalize(&mut *grid, out_vectors)
+        .iter()
+        .map(|transfer| transfer.moles)
+        .sum()
+}
+
+/// Equalize `grid` like `grid_equalize` does, also writing the net transfer over every edge gas moved through into `out_transfers`.
+/// A grid never has more than twice as many edges as it has turfs, so `out_len` has to be at least that to fit every transfer.
+/// Nothing is written if `out_transfers` is null or `out_len` is 0. Returns the amount of transfers.
+///
+/// # Safety
+/// `grid` and `out_vectors` have the same requirements as in `grid_equalize`, and `out_transfers` has to be null or point to `out_len` writable transfers.
+#[no_mangle]
+pub unsafe extern "C" fn grid_equalize_with_transfers(
+    grid: *mut Grid,
+    out_vectors: *mut f64,
+    out_transfers: *mut Transfer,
+    out_len: usize,
+) -> usize {
+    let out_vectors = std::slice::from_raw_parts_mut(out_vectors, (*grid).turfs.len() * 2);
+    let transfers = equalize(&mut *grid, out_vectors);
+
+    if !out_transfers.is_null() && out_len > 0 {
+        std::slice::from_raw_parts_mut(out_transfers, out_len)
+            .iter_mut()
+            .zip(transfers.iter())
+            .for_each(|(out, transfer)| *out = *transfer);
+    }
+
+    transfers.len()
+}
+
+/// Write the mixture of every turf of `grid` into `out_gas_mix`, closed turfs get `GasMixture::zero`
+///
+/// # Safety
+/// `grid` has to be a live handle from `grid_create`, `grid_load_dmm` or `grid_restore`, and `out_gas_mix` has to point to an array of `len` writable mixtures.
+#[no_mangle]
+pub unsafe extern "C" fn grid_read(grid: *const Grid, out_gas_mix: *const GasMixtureArrayFFI) {
+    (*out_gas_mix)
+        .as_slice_mut()
+        .iter_mut()
+        .zip((*grid).turfs.iter())
+        .for_each(|(out, turf)| *out = turf.air().unwrap_or_else(GasMixture::zero).into());
+}
+
+/// Save `grid` as a snapshot to the file at the null terminated `path`, returning whether it was written.
+/// Snapshots made over FFI hold either a grid, pipenets or a simulation, machines can only be snapshotted from Rust.
+///
+/// # Safety
+/// `grid` has to be a live handle from `grid_create`, `grid_load_dmm` or `grid_restore`, and `path` has to point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn grid_save(grid: *const Grid, path: *const c_char) -> bool {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+    let snapshot = Snapshot {
+        grid: Some((*grid).clone()),
+        ..Snapshot::default()
+    };
+
+    snapshot.save(path.as_ref()).is_ok()
+}
+
+/// Restore the grid of the snapshot at the null terminated `path`.
+/// Returns a null pointer if there's no grid to restore, otherwise the handle has to be freed with `grid_destroy`.
+///
+/// # Safety
+/// `path` has to point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn grid_restore(path: *const c_char) -> *mut Grid {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+
+    match Snapshot::load(path.as_ref()).map(|snapshot| snapshot.grid) {
+        Ok(Some(grid)) => Box::into_raw(Box::new(grid)),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Free a grid created by `grid_create`
+///
+/// # Safety
+/// `grid` has to be null or a live handle from `grid_create`, `grid_load_dmm` or `grid_restore`, it can't be used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn grid_destroy(grid: *mut Grid) {
+    if !grid.is_null() {
+        drop(Box::from_raw(grid));
+    }
+}
+
+/// Save the mixtures in `gas_mixes` as the pipenets of a snapshot to the file at the null terminated `path`, returning whether it was written
+///
+/// # Safety
+/// `gas_mixes` has to point to an array of `len` valid mixtures, and `path` to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pipenets_save(
+    gas_mixes: *const GasMixtureArrayFFI,
+    path: *const c_char,
+) -> bool {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+    let snapshot = Snapshot {
+        pipenets: (*gas_mixes).into(),
+        ..Snapshot::default()
+    };
+
+    snapshot.save(path.as_ref()).is_ok()
+}
+
+/// Restore the pipenets of the snapshot at the null terminated `path` into `out_gas_mix`, at most `out_gas_mix.len` of them.
+/// Returns the amount of pipenets in the snapshot, including the ones that didn't fit, or 0 if it couldn't be loaded.
+///
+/// # Safety
+/// `path` has to point to a null terminated string, and `out_gas_mix` to an array of `len` writable mixtures.
+#[no_mangle]
+pub unsafe extern "C" fn pipenets_restore(
+    path: *const c_char,
+    out_gas_mix: *const GasMixtureArrayFFI,
+) -> usize {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+    let pipenets = match Snapshot::load(path.as_ref()) {
+        Ok(snapshot) => snapshot.pipenets,
+        Err(_) => return 0,
+    };
+
+    (*out_gas_mix)
+        .as_slice_mut()
+        .iter_mut()
+        .zip(pipenets.iter())
+        .for_each(|(out, gm)| *out = (*gm).into());
+
+    pipenets.len()
+}
+
+/// Save `simulation` as a snapshot to the file at the null terminated `path`, returning whether it was written
+///
+/// # Safety
+/// `simulation` has to be a live handle from `simulation_create`, `simulation_create_seeded` or `simulation_restore`, and `path` has to point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_save(
+    simulation: *const Simulation,
+    path: *const c_char,
+) -> bool {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+    let snapshot = Snapshot {
+        simulations: vec![*simulation],
+        ..Snapshot::default()
+    };
+
+    snapshot.save(path.as_ref()).is_ok()
+}
+
+/// Restore the first simulation of the snapshot at the null terminated `path`, it resumes exactly where it was saved.
+/// Returns a null pointer if there's no simulation to restore, otherwise the handle has to be freed with `simulation_destroy`.
+///
+/// # Safety
+/// `path` has to point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn simulation_restore(path: *const c_char) -> *mut Simulation {
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+
+    match Snapshot::load(path.as_ref()).map(|snapshot| snapshot.simulations.into_iter().next()) {
+        Ok(Some(simulation)) => Box::into_raw(Box::new(simulation)),
+        _ => std::ptr::null_mut(),
+    }
+}
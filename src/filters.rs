@@ -0,0 +1,120 @@
+use crate::constants as C;
+use crate::gas::GasFilter;
+use crate::GasMixture;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScrubberMode {
+    /// Take only the filtered gases and put the rest back
+    Scrubbing,
+    /// Take in everything
+    Siphoning,
+}
+
+/// A vent scrubber pulling gas out of a room into its pipenet
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scrubber {
+    pub mode: ScrubberMode,
+    /// Liters of the room processed per tick
+    pub volume_rate: f64,
+    pub filter: GasFilter,
+}
+
+impl Scrubber {
+    pub fn new(mode: ScrubberMode, filter: GasFilter) -> Self {
+        Self {
+            mode,
+            volume_rate: C::MAX_TRANSFER_RATE,
+            filter,
+        }
+    }
+
+    /// Process `environment` for a tick, returning it and `pipenet` afterwards
+    pub fn process(
+        &self,
+        environment: &GasMixture,
+        pipenet: &GasMixture,
+    ) -> (GasMixture, GasMixture) {
+        if pipenet.get_pressure() >= C::SCRUBBER_MAXIMUM_PRESSURE || environment.volume <= 0. {
+            return (*environment, *pipenet);
+        }
+
+        match self.mode {
+            ScrubberMode::Scrubbing => {
+                if !environment
+                    .gases
+                    .0
+                    .iter()
+                    .any(|(gas, amount)| self.filter[gas] && *amount > 0.)
+                {
+                    return (*environment, *pipenet);
+                }
+
+                let (environment, removed) =
+                    environment.remove_ratio(self.volume_rate / environment.volume);
+                let (removed, filtered) = removed.remove_gases(&self.filter);
+
+                (environment.assume(&removed), pipenet.assume(&filtered))
+            }
+            ScrubberMode::Siphoning => {
+                let (environment, removed) =
+                    environment.remove_ratio(self.volume_rate / environment.volume);
+
+                (environment, pipenet.assume(&removed))
+            }
+        }
+    }
+}
+
+/// A gas filter splitting the filtered gases of its input off into a separate output
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Filter {
+    /// Liters of the input moved per tick
+    pub transfer_rate: f64,
+    pub filter: GasFilter,
+}
+
+impl Filter {
+    pub fn new(filter: GasFilter) -> Self {
+        Self {
+            transfer_rate: C::MAX_TRANSFER_RATE,
+            filter,
+        }
+    }
+
+    /// Process `input` for a tick, returning the input, the filtered output and the output afterwards.
+    /// Filtered gases stay in the input while the filtered output is over `MAX_OUTPUT_PRESSURE`.
+    pub fn process(
+        &self,
+        input: &GasMixture,
+        filtered_output: &GasMixture,
+        output: &GasMixture,
+    ) -> (GasMixture, GasMixture, GasMixture) {
+        if output.get_pressure() >= C::MAX_OUTPUT_PRESSURE
+            || input.temperature <= 0.
+            || input.volume <= 0.
+            || self.transfer_rate <= 0.
+        {
+            return (*input, *filtered_output, *output);
+        }
+
+        let (input, removed) = input.remove_ratio(self.transfer_rate / input.volume);
+        if removed.get_total_amount() <= 0. {
+            return (input, *filtered_output, *output);
+        }
+
+        let (removed, filtered) = removed.remove_gases(&self.filter);
+        if filtered_output.get_pressure() < C::MAX_OUTPUT_PRESSURE {
+            (
+                input,
+                filtered_output.assume(&filtered),
+                output.assume(&removed),
+            )
+        } else {
+            (
+                input.assume(&filtered),
+                *filtered_output,
+                output.assume(&removed),
+            )
+        }
+    }
+}
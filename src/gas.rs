@@ -1,9 +1,9 @@
 extern crate enum_map;
 
 use enum_map as EM;
-use std::ops::{Add, Index, Mul};
+use std::ops::{Add, Index, Mul, Sub};
 
-#[derive(Copy, Clone, Debug, EM::Enum)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EM::Enum)]
 #[repr(u8)]
 pub enum Gas {
     N2,
@@ -18,8 +18,9 @@ pub enum Gas {
     BZ,
     ST,
     PlOx,
+    Miasma,
 }
-pub const GAS_AMT: usize = 12;
+pub const GAS_AMT: usize = 13;
 
 impl Gas {
     fn heat_cap_of(self) -> f64 {
@@ -36,9 +37,41 @@ impl Gas {
             Gas::BZ => 0.,
             Gas::ST => 5.,
             Gas::PlOx => 80.,
+            Gas::Miasma => 20.,
         }
     }
 
+    /// Id of the gas in atmosphere strings, like `o2` in `o2=22;n2=82;TEMP=293.15`
+    pub fn id(self) -> &'static str {
+        match self {
+            Gas::N2 => "n2",
+            Gas::O2 => "o2",
+            Gas::CO2 => "co2",
+            Gas::N2O => "n2o",
+            Gas::Pl => "plasma",
+            Gas::H2O => "water_vapor",
+            Gas::HNb => "nob",
+            Gas::NO2 => "no2",
+            Gas::H2 => "tritium",
+            Gas::BZ => "bz",
+            Gas::ST => "stim",
+            Gas::PlOx => "pluox",
+            Gas::Miasma => "miasma",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        GasEnumMap::default()
+            .iter()
+            .map(|(gas, _)| gas)
+            .find(|gas| gas.id() == id)
+    }
+
+    /// Gas at `index` in the order of `Gas`, which is also the order of the FFI gas arrays
+    pub fn from_index(index: usize) -> Option<Self> {
+        GasEnumMap::default().iter().map(|(gas, _)| gas).nth(index)
+    }
+
     fn fusion_power_of(self) -> f64 {
         match self {
             Gas::N2O => 10.,
@@ -56,6 +89,10 @@ impl Gas {
 /// cbindgen:ignore
 pub type GasEnumMap = EM::EnumMap<Gas, f64>;
 
+/// Which gases a filter or scrubber picks out
+/// cbindgen:ignore
+pub type GasFilter = EM::EnumMap<Gas, bool>;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct GasVec(pub GasEnumMap);
 
@@ -77,6 +114,20 @@ impl GasVec {
     pub fn get_total_amount(&self) -> f64 {
         self.0.values().sum()
     }
+
+    /// Split off `ratio` of every gas, returning the rest and the removed part
+    pub fn remove_ratio(&self, ratio: f64) -> (Self, Self) {
+        let ratio = ratio.clamp(0., 1.);
+        (*self * (1. - ratio), *self * ratio)
+    }
+
+    /// Split off all of the gases in `filter`, returning the rest and the removed part
+    pub fn remove_gases(&self, filter: &GasFilter) -> (Self, Self) {
+        (
+            GasVec(GasEnumMap::from(|g| if filter[g] { 0. } else { self.0[g] })),
+            GasVec(GasEnumMap::from(|g| if filter[g] { self.0[g] } else { 0. })),
+        )
+    }
 }
 
 impl Add<GasVec> for GasVec {
@@ -86,6 +137,13 @@ impl Add<GasVec> for GasVec {
     }
 }
 
+impl Sub<GasVec> for GasVec {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        GasVec(GasEnumMap::from(|g| self.0[g] - rhs.0[g]))
+    }
+}
+
 impl Mul<f64> for GasVec {
     type Output = Self;
 
@@ -2,7 +2,28 @@ extern crate enum_map;
 
 use crate::{constants as C, gen_gas_vec};
 use crate::gas::*;
-use std::{ops::{Add, Index}};
+use std::{fmt, ops::{Add, Index}};
+
+/// Why an atmosphere string couldn't be parsed
+#[derive(Clone, Debug, PartialEq)]
+pub enum AtmosStringError {
+    /// An entry without an `=`
+    MissingValue(String),
+    UnknownGas(String),
+    InvalidNumber(String),
+}
+
+impl fmt::Display for AtmosStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AtmosStringError::MissingValue(entry) => write!(f, "no value in \"{}\"", entry),
+            AtmosStringError::UnknownGas(id) => write!(f, "unknown gas \"{}\"", id),
+            AtmosStringError::InvalidNumber(value) => write!(f, "invalid number \"{}\"", value),
+        }
+    }
+}
+
+impl std::error::Error for AtmosStringError {}
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct GasMixture {
@@ -56,6 +77,113 @@ impl GasMixture {
         }
     }
 
+    /// Split off `ratio` of the gases, returning the remaining mixture and the removed one.
+    /// Both keep the volume and temperature of the original mixture.
+    pub fn remove_ratio(&self, ratio: f64) -> (Self, Self) {
+        let (rest, removed) = self.gases.remove_ratio(ratio);
+        self.split_into(rest, removed)
+    }
+
+    /// Split off all of the gases in `filter`, returning the remaining mixture and the removed one.
+    /// Both keep the volume and temperature of the original mixture.
+    pub fn remove_gases(&self, filter: &GasFilter) -> (Self, Self) {
+        let (rest, removed) = self.gases.remove_gases(filter);
+        self.split_into(rest, removed)
+    }
+
+    fn split_into(&self, rest: GasVec, removed: GasVec) -> (Self, Self) {
+        (
+            Self {
+                gases: rest,
+                ..*self
+            },
+            Self {
+                gases: removed,
+                ..*self
+            },
+        )
+    }
+
+    /// Split off `amount` moles of gas, returning the remaining mixture and the removed one
+    pub fn remove(&self, amount: f64) -> (Self, Self) {
+        let total = self.get_total_amount();
+        if total > 0. {
+            self.remove_ratio(amount / total)
+        } else {
+            self.remove_ratio(0.)
+        }
+    }
+
+    /// Pump gas into `output` until it reaches `target_pressure`, like a canister or tank valve does, returning both mixtures afterwards.
+    /// Nothing moves if the output is already at the target or the pressure difference is under 10 kPa.
+    pub fn release_to(&self, output: &GasMixture, target_pressure: f64) -> (Self, Self) {
+        let output_pressure = output.get_pressure();
+        let input_pressure = self.get_pressure();
+
+        if output_pressure >= target_pressure.min(input_pressure - 10.)
+            || self.get_total_amount() <= 0.
+            || self.temperature <= 0.
+        {
+            return (*self, *output);
+        }
+
+        let pressure_delta =
+            (target_pressure - output_pressure).min((input_pressure - output_pressure) / 2.);
+        let transfer_moles =
+            pressure_delta * output.volume / (self.temperature * C::R_IDEAL_GAS_EQUATION);
+
+        let (rest, removed) = self.remove(transfer_moles);
+        (rest, output.assume(&removed))
+    }
+
+    /// Let gas flow freely between the two mixtures until their pressures are equal, returning both mixtures afterwards
+    pub fn equalize_with(&self, other: &GasMixture) -> (Self, Self) {
+        let volume = self.volume + other.volume;
+        if self.get_heat_cap() + other.get_heat_cap() == 0.0 || volume <= 0. {
+            return (*self, *other);
+        }
+
+        let combined = self.mix_with(other);
+        (
+            Self {
+                gases: combined.gases * (self.volume / volume),
+                volume: self.volume,
+                ..combined
+            },
+            Self {
+                gases: combined.gases * (other.volume / volume),
+                volume: other.volume,
+                ..combined
+            },
+        )
+    }
+
+    /// Merge `other` into this mixture without changing its volume
+    pub fn assume(&self, other: &GasMixture) -> Self {
+        if other.get_heat_cap() == 0.0 {
+            *self
+        } else if self.get_heat_cap() == 0.0 {
+            Self {
+                volume: self.volume,
+                ..*other
+            }
+        } else {
+            Self {
+                volume: self.volume,
+                ..self.mix_with(other)
+            }
+        }
+    }
+
+    /// Scale the change from this mixture to `other` by `factor`, keeping the volume of this mixture
+    pub fn scale_change_to(&self, other: &GasMixture, factor: f64) -> Self {
+        Self::with_energy(
+            self.gases + (other.gases - self.gases) * factor,
+            self.get_energy() + (other.get_energy() - self.get_energy()) * factor,
+            self.volume,
+        )
+    }
+
     pub fn with_energy(gases: GasVec, energy: f64, volume: f64) -> Self {
         if gases.get_heat_cap() == 0.0 {
             panic!("Null gas mixes may not have energy");
@@ -68,6 +196,62 @@ impl GasMixture {
         }
     }
 
+    /// Whether `sample` is different enough from this mixture for gas to move between them
+    pub fn differs_from(&self, sample: &GasMixture) -> bool {
+        let gas_differs = self.gases.0.iter().any(|(gas, amount)| {
+            let delta = (amount - sample[gas]).abs();
+            delta > C::MINIMUM_MOLES_DELTA_TO_MOVE && delta > amount * C::MINIMUM_AIR_RATIO_TO_MOVE
+        });
+
+        gas_differs
+            || (self.get_total_amount() > C::MINIMUM_MOLES_DELTA_TO_MOVE
+                && (self.temperature - sample.temperature).abs()
+                    > C::MINIMUM_TEMPERATURE_DELTA_TO_SUSPEND)
+    }
+
+    /// Parse an atmosphere string like `o2=22;n2=82;TEMP=293.15` into a mixture of `volume`.
+    /// The temperature defaults to `T20C` when there's no `TEMP` entry.
+    pub fn from_atmos_string(text: &str, volume: f64) -> Result<Self, AtmosStringError> {
+        let mut mixture = GasMixture {
+            gases: gen_gas_vec!(),
+            temperature: C::T20C,
+            volume,
+        };
+
+        for entry in text.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let mut parts = entry.splitn(2, '=');
+            let key = parts.next().unwrap_or_default().trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| AtmosStringError::MissingValue(entry.to_owned()))?
+                .trim();
+            let amount: f64 = value
+                .parse()
+                .map_err(|_| AtmosStringError::InvalidNumber(value.to_owned()))?;
+
+            if key == "TEMP" {
+                mixture.temperature = amount;
+            } else {
+                let gas = Gas::from_id(key).ok_or_else(|| AtmosStringError::UnknownGas(key.to_owned()))?;
+                mixture.gases.0[gas] = amount;
+            }
+        }
+
+        Ok(mixture)
+    }
+
+    /// Inverse of `from_atmos_string`, gases with no moles are left out
+    pub fn to_atmos_string(&self) -> String {
+        self.gases
+            .0
+            .iter()
+            .filter(|(_, amount)| **amount > 0.)
+            .map(|(gas, amount)| format!("{}={}", gas.id(), amount))
+            .chain(std::iter::once(format!("TEMP={}", self.temperature)))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
     pub fn zero() -> Self {
         GasMixture {
             gases: gen_gas_vec!(),
@@ -0,0 +1,384 @@
+use std::fmt;
+
+use crate::constants as C;
+use crate::gas_mixture::AtmosStringError;
+use crate::{reactions as R, Gas, GasMixture};
+
+/// How far a replayed value may be off from the recorded one.
+/// Errors are relative to the recorded value, or absolute when it's below 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tolerance {
+    pub moles: f64,
+    pub temperature: f64,
+}
+
+impl Default for Tolerance {
+    /// DM does its math in single precision, so recordings from a server can't be any more accurate than this
+    fn default() -> Self {
+        Self {
+            moles: 1e-4,
+            temperature: 1e-4,
+        }
+    }
+}
+
+/// A mixture and what it turned into after each of the `react()` calls that followed, as recorded somewhere else.
+///
+/// Traces are stored as blocks of text, the mixtures being atmosphere strings:
+/// ```text
+/// trace plasma_fire
+/// source where and how the trace was recorded
+/// reaction plasma_fire
+/// volume 2500
+/// input o2=100;plasma=10;TEMP=500
+/// output o2=97.6;plasma=8.1;co2=1.9;TEMP=823.1
+/// output o2=95.2;plasma=6.2;co2=3.8;TEMP=1121.9
+/// compare o2;plasma;co2
+/// mole_tolerance 0.0001
+/// temperature_tolerance 0.0001
+/// ```
+/// Everything but the name, input and outputs is optional, lines starting with `#` are comments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub name: String,
+    pub source: String,
+    /// Id of the only reaction every step runs, all of them run through `react_once` without one
+    pub reaction: Option<String>,
+    pub input: GasMixture,
+    /// The mixture after every step, in order
+    pub outputs: Vec<GasMixture>,
+    /// Gases the outputs are compared on, along with the temperature. Without a list every gas is, the ones left out of an output having to run out.
+    pub compared: Option<Vec<Gas>>,
+    pub tolerance: Tolerance,
+}
+
+/// Why traces couldn't be parsed, with the line it happened on, counting from 1
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceError {
+    /// A line that isn't a known entry, or an entry outside of a trace
+    UnexpectedLine(usize),
+    InvalidAtmosphere(usize, AtmosStringError),
+    InvalidNumber(usize),
+    UnknownGas(usize),
+    UnknownReaction(usize),
+    /// A trace without an input or any outputs, by name
+    Incomplete(String),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceError::UnexpectedLine(line) => write!(f, "unexpected line {}", line),
+            TraceError::InvalidAtmosphere(line, error) => {
+                write!(f, "invalid atmosphere on line {}: {}", line, error)
+            }
+            TraceError::InvalidNumber(line) => write!(f, "invalid number on line {}", line),
+            TraceError::UnknownGas(line) => write!(f, "unknown gas on line {}", line),
+            TraceError::UnknownReaction(line) => write!(f, "unknown reaction on line {}", line),
+            TraceError::Incomplete(name) => write!(f, "trace \"{}\" is incomplete", name),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+/// How far a single value of a replayed step was from the recording
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deviation {
+    /// Step of the trace, counting from 1
+    pub step: usize,
+    /// Id of the gas, or `TEMP` for the temperature
+    pub quantity: String,
+    pub expected: f64,
+    pub actual: f64,
+    pub error: f64,
+    pub within_tolerance: bool,
+}
+
+/// Every value a replayed trace recorded, compared with what `react_once` made of it
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceReport {
+    pub name: String,
+    pub source: String,
+    pub deviations: Vec<Deviation>,
+}
+
+impl TraceReport {
+    pub fn passed(&self) -> bool {
+        self.deviations
+            .iter()
+            .all(|deviation| deviation.within_tolerance)
+    }
+
+    /// The largest error of each quantity over the whole trace
+    pub fn worst_errors(&self) -> Vec<(&str, f64)> {
+        let mut worst: Vec<(&str, f64)> = Vec::new();
+        for deviation in &self.deviations {
+            match worst
+                .iter_mut()
+                .find(|(quantity, _)| *quantity == deviation.quantity)
+            {
+                Some((_, error)) => *error = error.max(deviation.error),
+                None => worst.push((&deviation.quantity, deviation.error)),
+            }
+        }
+
+        worst
+    }
+}
+
+impl fmt::Display for TraceReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} ({}): {}",
+            self.name,
+            self.source,
+            if self.passed() { "passed" } else { "FAILED" }
+        )?;
+        for deviation in &self.deviations {
+            writeln!(
+                f,
+                "  step {:>3} {:>12} expected {:>24e} actual {:>24e} error {:>10.3e}{}",
+                deviation.step,
+                deviation.quantity,
+                deviation.expected,
+                deviation.actual,
+                deviation.error,
+                if deviation.within_tolerance {
+                    ""
+                } else {
+                    " OUT OF TOLERANCE"
+                }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn error(expected: f64, actual: f64) -> f64 {
+    (actual - expected).abs() / expected.abs().max(1.)
+}
+
+fn parse_number(line: usize, value: &str) -> Result<f64, TraceError> {
+    value.parse().map_err(|_| TraceError::InvalidNumber(line))
+}
+
+fn parse_gases(line: usize, value: &str) -> Result<Vec<Gas>, TraceError> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| Gas::from_id(id).ok_or(TraceError::UnknownGas(line)))
+        .collect()
+}
+
+fn find_reaction(id: &str) -> Option<&'static R::Reaction> {
+    R::REACTIONS.iter().find(|reaction| reaction.id == id)
+}
+
+/// Parse every trace in `text`
+pub fn parse_traces(text: &str) -> Result<Vec<Trace>, TraceError> {
+    // Atmosphere strings are parsed once the volume of the trace is known
+    struct Pending<'a> {
+        name: &'a str,
+        source: &'a str,
+        reaction: Option<&'a str>,
+        volume: f64,
+        input: Option<(usize, &'a str)>,
+        outputs: Vec<(usize, &'a str)>,
+        compared: Option<Vec<Gas>>,
+        tolerance: Tolerance,
+    }
+
+    fn finish(pending: Pending) -> Result<Trace, TraceError> {
+        let mixture = |(line, text): (usize, &str)| {
+            GasMixture::from_atmos_string(text, pending.volume)
+                .map_err(|error| TraceError::InvalidAtmosphere(line, error))
+        };
+        let input = pending
+            .input
+            .ok_or_else(|| TraceError::Incomplete(pending.name.to_owned()))?;
+        if pending.outputs.is_empty() {
+            return Err(TraceError::Incomplete(pending.name.to_owned()));
+        }
+
+        Ok(Trace {
+            name: pending.name.to_owned(),
+            source: pending.source.to_owned(),
+            reaction: pending.reaction.map(str::to_owned),
+            input: mixture(input)?,
+            outputs: pending
+                .outputs
+                .iter()
+                .map(|&output| mixture(output))
+                .collect::<Result<_, _>>()?,
+            compared: pending.compared,
+            tolerance: pending.tolerance,
+        })
+    }
+
+    let mut traces = Vec::new();
+    let mut current: Option<Pending> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = match line.find(' ') {
+            Some(space) => (&line[..space], line[space..].trim()),
+            None => (line, ""),
+        };
+
+        if key == "trace" {
+            if let Some(pending) = current.take() {
+                traces.push(finish(pending)?);
+            }
+            current = Some(Pending {
+                name: value,
+                source: "",
+                reaction: None,
+                volume: C::CELL_VOLUME,
+                input: None,
+                outputs: Vec::new(),
+                compared: None,
+                tolerance: Tolerance::default(),
+            });
+            continue;
+        }
+
+        let pending = current.as_mut().ok_or(TraceError::UnexpectedLine(number))?;
+        match key {
+            "source" => pending.source = value,
+            "reaction" => {
+                find_reaction(value).ok_or(TraceError::UnknownReaction(number))?;
+                pending.reaction = Some(value);
+            }
+            "volume" => pending.volume = parse_number(number, value)?,
+            "input" => pending.input = Some((number, value)),
+            "output" => pending.outputs.push((number, value)),
+            "compare" => pending.compared = Some(parse_gases(number, value)?),
+            "mole_tolerance" => pending.tolerance.moles = parse_number(number, value)?,
+            "temperature_tolerance" => pending.tolerance.temperature = parse_number(number, value)?,
+            _ => return Err(TraceError::UnexpectedLine(number)),
+        }
+    }
+
+    if let Some(pending) = current {
+        traces.push(finish(pending)?);
+    }
+
+    Ok(traces)
+}
+
+/// Write `traces` in the format `parse_traces` reads, every number is written exactly
+pub fn write_traces(traces: &[Trace]) -> String {
+    let mut text = String::new();
+
+    for trace in traces {
+        text += &format!("trace {}\n", trace.name);
+        text += &format!("source {}\n", trace.source);
+        if let Some(reaction) = &trace.reaction {
+            text += &format!("reaction {}\n", reaction);
+        }
+        text += &format!("volume {}\n", trace.input.volume);
+        text += &format!("input {}\n", trace.input.to_atmos_string());
+        for output in &trace.outputs {
+            text += &format!("output {}\n", output.to_atmos_string());
+        }
+        if let Some(compared) = &trace.compared {
+            let ids: Vec<&str> = compared.iter().map(|gas| gas.id()).collect();
+            text += &format!("compare {}\n", ids.join(";"));
+        }
+        if trace.tolerance != Tolerance::default() {
+            text += &format!("mole_tolerance {}\n", trace.tolerance.moles);
+            text += &format!("temperature_tolerance {}\n", trace.tolerance.temperature);
+        }
+        text += "\n";
+    }
+
+    text
+}
+
+impl Trace {
+    /// Record `steps` calls of `react_once` on `input`, for traces of this crate's own behaviour
+    pub fn record(name: &str, source: &str, input: GasMixture, steps: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            source: source.to_owned(),
+            reaction: None,
+            input,
+            outputs: R::react_several(input, steps),
+            compared: None,
+            tolerance: Tolerance::default(),
+        }
+    }
+
+    /// React the input once for every recorded step and compare the gases and the temperature with the recording.
+    /// Every step starts from the recorded mixture of the step before, so an early deviation doesn't carry over.
+    /// Traces of reactions that don't exist replay as if nothing reacted.
+    pub fn replay(&self) -> TraceReport {
+        let reaction = self.reaction.as_deref().map(find_reaction);
+        let mut deviations = Vec::new();
+        let mut previous = self.input;
+
+        for (index, expected) in self.outputs.iter().enumerate() {
+            let actual = match reaction {
+                Some(Some(reaction)) => {
+                    (reaction.react)(previous, &mut R::ReactionOutput::default(), None)
+                }
+                Some(None) => previous,
+                None => R::react_once(previous),
+            };
+
+            for ((gas, &expected_amount), (_, &actual_amount)) in
+                expected.gases.0.iter().zip(actual.gases.0.iter())
+            {
+                let skipped = match &self.compared {
+                    Some(compared) => !compared.contains(&gas),
+                    None => expected_amount == 0. && actual_amount == 0.,
+                };
+                if skipped {
+                    continue;
+                }
+
+                let error = error(expected_amount, actual_amount);
+                deviations.push(Deviation {
+                    step: index + 1,
+                    quantity: gas.id().to_owned(),
+                    expected: expected_amount,
+                    actual: actual_amount,
+                    error,
+                    within_tolerance: error <= self.tolerance.moles,
+                });
+            }
+
+            let error = error(expected.temperature, actual.temperature);
+            deviations.push(Deviation {
+                step: index + 1,
+                quantity: "TEMP".to_owned(),
+                expected: expected.temperature,
+                actual: actual.temperature,
+                error,
+                within_tolerance: error <= self.tolerance.temperature,
+            });
+
+            previous = *expected;
+        }
+
+        TraceReport {
+            name: self.name.clone(),
+            source: self.source.clone(),
+            deviations,
+        }
+    }
+}
+
+/// Parse and replay every trace in `text`
+pub fn replay_traces(text: &str) -> Result<Vec<TraceReport>, TraceError> {
+    Ok(parse_traces(text)?.iter().map(Trace::replay).collect())
+}
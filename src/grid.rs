@@ -0,0 +1,504 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::constants as C;
+use crate::gas::GasEnumMap;
+use crate::gas_mixture::AtmosStringError;
+use crate::{gen_gas_vec, GasMixture, GasVec};
+
+/// Adjacent turfs a planetary turf is considered to have when it shares with its atmosphere
+const PLANETARY_ADJACENT_TURFS: usize = 4;
+
+/// A single tile of a grid and how it behaves towards its neighbours
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Turf {
+    /// Walls and anything else gas can't pass through
+    Closed,
+    Open(GasMixture),
+    /// Vacuum at `TCMB` swallowing whatever flows into it
+    Space,
+    /// An open turf that keeps sharing with a fixed atmosphere, like lavaland or the icemoon
+    Planetary {
+        air: GasMixture,
+        atmosphere: GasMixture,
+    },
+    /// A turf whose mixture never changes, no matter what flows in or out
+    Immutable(GasMixture),
+}
+
+fn space_air() -> GasMixture {
+    GasMixture {
+        gases: gen_gas_vec!(),
+        temperature: C::TCMB,
+        volume: C::CELL_VOLUME,
+    }
+}
+
+impl Turf {
+    /// An open turf with the mixture of an atmosphere string
+    pub fn open(atmosphere: &str) -> Result<Self, AtmosStringError> {
+        Ok(Turf::Open(GasMixture::from_atmos_string(
+            atmosphere,
+            C::CELL_VOLUME,
+        )?))
+    }
+
+    /// A planetary turf starting out with the atmosphere it's replenished from
+    pub fn planetary(atmosphere: &str) -> Result<Self, AtmosStringError> {
+        let atmosphere = GasMixture::from_atmos_string(atmosphere, C::CELL_VOLUME)?;
+        Ok(Turf::Planetary {
+            air: atmosphere,
+            atmosphere,
+        })
+    }
+
+    pub fn air(&self) -> Option<GasMixture> {
+        match *self {
+            Turf::Closed => None,
+            Turf::Open(air) | Turf::Planetary { air, .. } | Turf::Immutable(air) => Some(air),
+            Turf::Space => Some(space_air()),
+        }
+    }
+
+    /// Whether the mixture of the turf changes when it shares with its neighbours
+    pub fn is_simulated(&self) -> bool {
+        matches!(self, Turf::Open(_) | Turf::Planetary { .. })
+    }
+
+    /// Planetary turfs stay awake until they're back to their atmosphere
+    fn needs_replenishing(&self) -> bool {
+        match self {
+            Turf::Planetary { air, atmosphere } => air.differs_from(atmosphere),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn set_air(&mut self, new_air: GasMixture) {
+        match self {
+            Turf::Open(air) | Turf::Planetary { air, .. } => *air = new_air,
+            _ => (),
+        }
+    }
+}
+
+/// Gas and thermal energy moving from `air` into `sharer` when they share, based on LINDA's share.
+/// `adjacent_turfs` is the amount of neighbours the gas is split between.
+fn share(air: &GasMixture, sharer: &GasMixture, adjacent_turfs: usize) -> (GasVec, f64) {
+    let moved = (air.gases - sharer.gases) * (1. / (adjacent_turfs + 1) as f64);
+    let outflow = GasVec(GasEnumMap::from(|gas| moved[gas].max(0.)));
+    let inflow = GasVec(GasEnumMap::from(|gas| (-moved[gas]).max(0.)));
+
+    let mut energy =
+        outflow.get_heat_cap() * air.temperature - inflow.get_heat_cap() * sharer.temperature;
+
+    let temperature_delta = air.temperature - sharer.temperature;
+    let heat_capacity = air.get_heat_cap();
+    let sharer_heat_capacity = sharer.get_heat_cap();
+    let new_sharer_heat_capacity =
+        sharer_heat_capacity + outflow.get_heat_cap() - inflow.get_heat_cap();
+    if temperature_delta.abs() > C::MINIMUM_TEMPERATURE_DELTA_TO_CONSIDER
+        && heat_capacity > C::MINIMUM_HEAT_CAPACITY
+        && sharer_heat_capacity > C::MINIMUM_HEAT_CAPACITY
+        && (new_sharer_heat_capacity / sharer_heat_capacity - 1.).abs() < 0.1
+    {
+        energy += C::OPEN_HEAT_TRANSFER_COEFFICIENT
+            * temperature_delta
+            * (heat_capacity * sharer_heat_capacity / (heat_capacity + sharer_heat_capacity));
+    }
+
+    (moved, energy)
+}
+
+/// Turfs that keep sharing with each other until they settle down, see LINDA's excited groups
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExcitedGroup {
+    pub turfs: Vec<usize>,
+    /// Ticks since the group last moved a significant amount of gas, the group averages out its mixtures when this gets high enough
+    pub breakdown_cooldown: u32,
+    /// Same as `breakdown_cooldown`, but the group falls asleep when this gets high enough
+    pub dismantle_cooldown: u32,
+}
+
+impl ExcitedGroup {
+    fn reset_cooldowns(&mut self) {
+        self.breakdown_cooldown = 0;
+        self.dismantle_cooldown = 0;
+    }
+}
+
+/// Gas and energy a turf exchanged with its neighbours during a tick
+#[derive(Copy, Clone)]
+struct Exchange {
+    gases: GasVec,
+    energy: f64,
+    /// Moles moved in either direction
+    moles: f64,
+}
+
+impl Default for Exchange {
+    fn default() -> Self {
+        Self {
+            gases: gen_gas_vec!(),
+            energy: 0.,
+            moles: 0.,
+        }
+    }
+}
+
+/// How much work a tick of a grid took
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct TickStats {
+    /// Turfs processed during the tick
+    pub active_turfs: usize,
+    /// Mixtures read during the tick, a turf is counted once for every neighbour it's compared with
+    pub mixtures_read: usize,
+    /// Excited groups left after the tick
+    pub excited_groups: usize,
+    /// Pairs of turfs that shared gas
+    pub shares: usize,
+}
+
+/// A rectangular map of turfs sharing gas with their four direct neighbours.
+/// Only active turfs are processed, they fall asleep once their mixtures settle like in LINDA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    /// Turfs row by row, starting at the top left
+    pub turfs: Vec<Turf>,
+    /// Indices of the turfs that get processed on the next tick, a tick does no work for the others
+    pub active: BTreeSet<usize>,
+    pub excited_groups: Vec<ExcitedGroup>,
+    /// Whether an excited group with space in it is emptied when it breaks down, instead of draining into space by sharing.
+    /// Off by default, like `space_is_all_consuming` in LINDA.
+    pub space_is_all_consuming: bool,
+    /// Index of the excited group of every turf
+    group_of: Vec<Option<usize>>,
+}
+
+impl Grid {
+    /// A grid filled with `turf`, with every turf active
+    pub fn new(width: usize, height: usize, turf: Turf) -> Self {
+        Self {
+            width,
+            height,
+            turfs: vec![turf; width * height],
+            active: (0..width * height).collect(),
+            excited_groups: Vec::new(),
+            space_is_all_consuming: false,
+            group_of: vec![None; width * height],
+        }
+    }
+
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &Turf {
+        &self.turfs[self.index(x, y)]
+    }
+
+    /// Replace the turf at `x`, `y`, waking it and its neighbours up
+    pub fn set(&mut self, x: usize, y: usize, turf: Turf) {
+        let index = self.index(x, y);
+        self.turfs[index] = turf;
+        self.activate(index);
+    }
+
+    /// Wake up the turf at `index` and its neighbours, needed after changing a turf through `turfs`
+    pub fn activate(&mut self, index: usize) {
+        self.active.insert(index);
+        let (neighbours, count) = self.adjacent(index);
+        self.active.extend(&neighbours[..count]);
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active
+            .iter()
+            .filter(|&&index| self.turfs[index].is_simulated())
+            .count()
+    }
+
+    /// Indices of the turfs next to the turf at `index` that gas can flow into
+    pub fn neighbours(&self, index: usize) -> Vec<usize> {
+        let (neighbours, count) = self.adjacent(index);
+        neighbours[..count].to_vec()
+    }
+
+    /// Same as `neighbours`, without allocating. Only the first `count` of the indices are neighbours.
+    fn adjacent(&self, index: usize) -> ([usize; 4], usize) {
+        let (x, y) = (index % self.width, index / self.width);
+        let mut neighbours = [0; 4];
+        let mut count = 0;
+        let mut push = |neighbour: usize| {
+            if self.turfs[neighbour] != Turf::Closed {
+                neighbours[count] = neighbour;
+                count += 1;
+            }
+        };
+
+        if y > 0 {
+            push(index - self.width);
+        }
+        if x + 1 < self.width {
+            push(index + 1);
+        }
+        if y + 1 < self.height {
+            push(index + self.width);
+        }
+        if x > 0 {
+            push(index - 1);
+        }
+
+        (neighbours, count)
+    }
+
+    /// Total amount of moles in the simulated turfs
+    pub fn total_moles(&self) -> f64 {
+        self.turfs
+            .iter()
+            .filter(|turf| turf.is_simulated())
+            .filter_map(Turf::air)
+            .map(|air| air.get_total_amount())
+            .sum()
+    }
+
+    /// Let every active turf share with the neighbours it differs from, then planetary turfs share with their atmospheres.
+    /// Sharing is based on the mixtures at the start of the tick, so the order of the turfs doesn't matter.
+    /// Each pair splits its gas by the larger of their neighbour counts, so no turf can give away more than it has.
+    /// Afterwards the excited groups average out or fall asleep once they've stopped moving gas for long enough.
+    pub fn tick(&mut self) -> TickStats {
+        let mut exchanges: HashMap<usize, Exchange> = HashMap::new();
+        let mut woken = Vec::new();
+        let mut stats = TickStats::default();
+        let active: Vec<usize> = self.active.iter().copied().collect();
+
+        for index in active {
+            if !self.turfs[index].is_simulated() {
+                continue;
+            }
+            // Nothing changes until every turf is done, so the mixtures read are the ones from the start of the tick
+            let air = self.turfs[index].air().unwrap();
+            let (neighbours, count) = self.adjacent(index);
+            stats.active_turfs += 1;
+            stats.mixtures_read += 1;
+
+            for &neighbour in &neighbours[..count] {
+                let simulated = self.turfs[neighbour].is_simulated();
+                if simulated && self.active.contains(&neighbour) && neighbour < index {
+                    continue;
+                }
+
+                let enemy_air = self.turfs[neighbour].air().unwrap();
+                stats.mixtures_read += 1;
+                let should_share = match (self.group_of[index], self.group_of[neighbour]) {
+                    (Some(ours), Some(theirs)) => {
+                        self.merge_groups(ours, theirs);
+                        true
+                    }
+                    _ if air.differs_from(&enemy_air) => {
+                        if simulated && !self.active.contains(&neighbour) {
+                            woken.push(neighbour);
+                        }
+                        self.join_group(index, neighbour);
+                        true
+                    }
+                    _ => false,
+                };
+
+                if should_share {
+                    let adjacent_turfs = count.max(self.adjacent(neighbour).1);
+                    let (moved, energy) = share(&air, &enemy_air, adjacent_turfs);
+                    let moved_moles = moved.0.values().map(|delta| delta.abs()).sum::<f64>();
+
+                    let ours = exchanges.entry(index).or_default();
+                    ours.gases = ours.gases - moved;
+                    ours.energy -= energy;
+                    ours.moles += moved_moles;
+                    let theirs = exchanges.entry(neighbour).or_default();
+                    theirs.gases = theirs.gases + moved;
+                    theirs.energy += energy;
+                    theirs.moles += moved_moles;
+                    stats.shares += 1;
+                }
+            }
+
+            if let Turf::Planetary { atmosphere, .. } = self.turfs[index] {
+                if air.differs_from(&atmosphere) {
+                    let (moved, energy) = share(&air, &atmosphere, PLANETARY_ADJACENT_TURFS);
+                    let ours = exchanges.entry(index).or_default();
+                    ours.gases = ours.gases - moved;
+                    ours.energy -= energy;
+                    ours.moles += moved.0.values().map(|delta| delta.abs()).sum::<f64>();
+                }
+            }
+        }
+
+        for (&index, exchange) in &exchanges {
+            let turf = &mut self.turfs[index];
+            if turf.is_simulated() {
+                let air = turf.air().unwrap();
+                turf.set_air(apply_share(&air, exchange.gases, exchange.energy));
+            }
+        }
+
+        let group_of = &self.group_of;
+        self.active
+            .retain(|index| exchanges.contains_key(index) || group_of[*index].is_some());
+        self.active.extend(woken);
+
+        self.process_excited_groups(&exchanges);
+        stats.excited_groups = self.excited_groups.len();
+
+        stats
+    }
+
+    /// Tick `ticks` times, returning the stats of every tick
+    pub fn run(&mut self, ticks: usize) -> Vec<TickStats> {
+        (1..=ticks).map(|_| self.tick()).collect()
+    }
+
+    /// Put `neighbour` into the group of `index`, creating a group if neither of them is in one
+    fn join_group(&mut self, index: usize, neighbour: usize) {
+        let group = match self.group_of[index].or(self.group_of[neighbour]) {
+            Some(group) => group,
+            None => {
+                self.excited_groups.push(ExcitedGroup::default());
+                self.excited_groups.len() - 1
+            }
+        };
+
+        for turf in [index, neighbour].iter() {
+            if self.group_of[*turf].is_none() {
+                self.group_of[*turf] = Some(group);
+                self.excited_groups[group].turfs.push(*turf);
+            }
+        }
+    }
+
+    /// Move the turfs of the smaller group into the larger one, the smaller one is left empty
+    fn merge_groups(&mut self, ours: usize, theirs: usize) {
+        if ours == theirs {
+            return;
+        }
+
+        let (into, from) =
+            if self.excited_groups[ours].turfs.len() >= self.excited_groups[theirs].turfs.len() {
+                (ours, theirs)
+            } else {
+                (theirs, ours)
+            };
+
+        let turfs = std::mem::take(&mut self.excited_groups[from].turfs);
+        turfs
+            .iter()
+            .for_each(|&turf| self.group_of[turf] = Some(into));
+        self.excited_groups[into].turfs.extend(turfs);
+        self.excited_groups[into].reset_cooldowns();
+    }
+
+    fn process_excited_groups(&mut self, exchanges: &HashMap<usize, Exchange>) {
+        let mut groups = std::mem::take(&mut self.excited_groups);
+        groups.retain(|group| !group.turfs.is_empty());
+
+        for mut group in groups {
+            if group.turfs.iter().any(|turf| {
+                exchanges
+                    .get(turf)
+                    .is_some_and(|exchange| exchange.moles > C::MINIMUM_AIR_TO_SUSPEND)
+            }) {
+                group.reset_cooldowns();
+            }
+            group.breakdown_cooldown += 1;
+            group.dismantle_cooldown += 1;
+
+            if group.breakdown_cooldown >= C::EXCITED_GROUP_BREAKDOWN_CYCLES {
+                self.self_breakdown(&group);
+                group.breakdown_cooldown = 0;
+            } else if group.dismantle_cooldown >= C::EXCITED_GROUP_DISMANTLE_CYCLES {
+                for &turf in &group.turfs {
+                    self.group_of[turf] = None;
+                    if !self.turfs[turf].needs_replenishing() {
+                        self.active.remove(&turf);
+                    }
+                }
+                continue;
+            }
+
+            self.excited_groups.push(group);
+        }
+
+        self.index_groups();
+    }
+
+    /// Point every turf of the excited groups to its group, needed whenever groups are added or removed
+    pub(crate) fn index_groups(&mut self) {
+        let group_of = &mut self.group_of;
+        for (index, group) in self.excited_groups.iter().enumerate() {
+            group
+                .turfs
+                .iter()
+                .for_each(|&turf| group_of[turf] = Some(index));
+        }
+    }
+
+    /// Give every turf of `group` the average of their mixtures, space is left out and keeps draining them by sharing.
+    /// With `space_is_all_consuming` a group with space in it is left as vacuum instead.
+    fn self_breakdown(&mut self, group: &ExcitedGroup) {
+        let has_space = group
+            .turfs
+            .iter()
+            .any(|&turf| self.turfs[turf] == Turf::Space);
+        let average = if has_space && self.space_is_all_consuming {
+            space_air()
+        } else {
+            let airs: Vec<GasMixture> = group
+                .turfs
+                .iter()
+                .filter(|&&turf| self.turfs[turf] != Turf::Space)
+                .filter_map(|&turf| self.turfs[turf].air())
+                .collect();
+            if airs.is_empty() {
+                return;
+            }
+            let gases = airs
+                .iter()
+                .fold(gen_gas_vec!(), |total, air| total + air.gases);
+            let energy: f64 = airs.iter().map(|air| air.get_energy()).sum();
+            let heat_capacity = gases.get_heat_cap();
+            if heat_capacity <= C::MINIMUM_HEAT_CAPACITY {
+                return;
+            }
+
+            GasMixture {
+                gases: gases * (1. / airs.len() as f64),
+                temperature: energy / heat_capacity,
+                volume: C::CELL_VOLUME,
+            }
+        };
+
+        for &turf in &group.turfs {
+            if let Some(air) = self.turfs[turf].air() {
+                self.turfs[turf].set_air(GasMixture {
+                    volume: air.volume,
+                    ..average
+                });
+            }
+        }
+    }
+}
+
+fn apply_share(air: &GasMixture, gas_delta: GasVec, energy_delta: f64) -> GasMixture {
+    let gases = air.gases + gas_delta;
+    let heat_capacity = gases.get_heat_cap();
+
+    GasMixture {
+        gases,
+        temperature: if heat_capacity > C::MINIMUM_HEAT_CAPACITY {
+            ((air.get_energy() + energy_delta) / heat_capacity).max(C::TCMB)
+        } else {
+            air.temperature
+        },
+        volume: air.volume,
+    }
+}
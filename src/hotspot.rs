@@ -0,0 +1,226 @@
+use crate::constants as C;
+use crate::rng::{self, Rng};
+use crate::{reactions as R, Gas, GasMixture};
+
+/// Fuel and oxygen below this amount can't sustain a hotspot
+const HOTSPOT_MINIMUM_GAS: f64 = 0.5;
+
+/// A fire burning in a part of a gas mixture
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hotspot {
+    pub volume: f64,
+    pub temperature: f64,
+    /// Freshly spawned hotspots skip their first process
+    pub just_spawned: bool,
+    /// Whether the hotspot engulfed the whole mixture and burns it all at once
+    pub bypassing: bool,
+}
+
+fn has_oxygen(air: &GasMixture) -> bool {
+    air[Gas::O2] >= HOTSPOT_MINIMUM_GAS
+}
+
+fn has_fuel(air: &GasMixture) -> bool {
+    air[Gas::Pl] > HOTSPOT_MINIMUM_GAS || air[Gas::H2] > HOTSPOT_MINIMUM_GAS
+}
+
+impl Hotspot {
+    pub fn new(volume: f64, temperature: f64) -> Self {
+        Self {
+            volume,
+            temperature,
+            just_spawned: true,
+            bypassing: false,
+        }
+    }
+
+    /// Expose `air` to a heat source, returning the hotspot it ignites, if any, and the mixture after the first burn
+    pub fn ignite(
+        air: GasMixture,
+        exposed_temperature: f64,
+        exposed_volume: f64,
+    ) -> (Option<Self>, GasMixture) {
+        if has_oxygen(&air)
+            && has_fuel(&air)
+            && exposed_temperature > C::PLASMA_MINIMUM_BURN_TEMPERATURE
+        {
+            let (hotspot, air) =
+                Self::new(exposed_volume * 25., exposed_temperature).perform_exposure(air, 0.);
+            (Some(hotspot), air)
+        } else {
+            (None, air)
+        }
+    }
+
+    /// Expose an already burning hotspot to a heat source, it can only get hotter and larger
+    pub fn stoke(self, air: &GasMixture, exposed_temperature: f64, exposed_volume: f64) -> Self {
+        if has_oxygen(air) && has_fuel(air) {
+            Self {
+                temperature: self.temperature.max(exposed_temperature),
+                volume: self.volume.max(exposed_volume),
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Burn the part of `air` covered by the hotspot.
+    /// `fire` is the amount of fuel the whole mixture burned on its own this tick, which drives a bypassing hotspot.
+    pub fn perform_exposure(self, air: GasMixture, fire: f64) -> (Self, GasMixture) {
+        let bypassing = !self.just_spawned && self.volume > C::CELL_VOLUME * 0.95;
+
+        if bypassing {
+            (
+                Self {
+                    volume: fire * C::FIRE_GROWTH_RATE,
+                    temperature: air.temperature,
+                    bypassing,
+                    ..self
+                },
+                air,
+            )
+        } else {
+            if air.volume <= 0. {
+                return (Self { bypassing, ..self }, air);
+            }
+
+            let (rest, affected) = air.remove_ratio((self.volume / air.volume).clamp(0., 1.));
+
+            if affected.get_heat_cap() == 0.0 {
+                return (Self { bypassing, ..self }, air);
+            }
+
+            let (affected, output) = R::react_once_with_output(GasMixture {
+                temperature: self.temperature,
+                ..affected
+            });
+
+            (
+                Self {
+                    volume: output.fire * C::FIRE_GROWTH_RATE,
+                    temperature: affected.temperature,
+                    bypassing,
+                    ..self
+                },
+                rest.assume(&affected),
+            )
+        }
+    }
+
+    /// Process the hotspot for a single tick, returning `None` in place of the hotspot if it burned out
+    pub fn process(self, air: GasMixture, fire: f64) -> (Option<Self>, GasMixture) {
+        if self.just_spawned {
+            return (
+                Some(Self {
+                    just_spawned: false,
+                    ..self
+                }),
+                air,
+            );
+        }
+
+        if self.temperature < C::FIRE_MINIMUM_TEMPERATURE_TO_EXIST
+            || self.volume <= 1.
+            || !has_oxygen(&air)
+            || !has_fuel(&air)
+        {
+            return (None, air);
+        }
+
+        let (hotspot, air) = self.perform_exposure(air, fire);
+        (Some(hotspot), air)
+    }
+
+    /// Temperature the hotspot radiates onto the neighbouring mixtures, if it's hot enough to spread
+    pub fn radiated_temperature(&self, air: &GasMixture) -> Option<f64> {
+        if self.bypassing && air.temperature > C::FIRE_MINIMUM_TEMPERATURE_TO_SPREAD {
+            Some(air.temperature * C::FIRE_SPREAD_RADIOSITY_SCALE)
+        } else {
+            None
+        }
+    }
+}
+
+/// A set of connected gas mixtures fire can spread across
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FireArea {
+    pub airs: Vec<GasMixture>,
+    pub hotspots: Vec<Option<Hotspot>>,
+    /// Indices of the mixtures adjacent to each mixture
+    pub adjacency: Vec<Vec<usize>>,
+    /// Percent chance of a bypassing hotspot to spread to each of its neighbours every tick
+    pub spread_chance: f64,
+    /// Generator the reactions and spreading roll their chances with, every roll passes without one
+    pub rng: Option<Rng>,
+}
+
+impl FireArea {
+    pub fn new(airs: Vec<GasMixture>, adjacency: Vec<Vec<usize>>) -> Self {
+        Self {
+            hotspots: vec![None; airs.len()],
+            airs,
+            adjacency,
+            spread_chance: 100.,
+            rng: None,
+        }
+    }
+
+    /// Expose the mixture at `index` to a heat source, igniting or stoking its hotspot
+    pub fn expose(&mut self, index: usize, exposed_temperature: f64, exposed_volume: f64) {
+        let air = self.airs[index];
+        let (hotspot, air) = match self.hotspots[index] {
+            Some(hotspot) => (
+                Some(hotspot.stoke(&air, exposed_temperature, exposed_volume)),
+                air,
+            ),
+            None => Hotspot::ignite(air, exposed_temperature, exposed_volume),
+        };
+
+        self.hotspots[index] = hotspot;
+        self.airs[index] = air;
+    }
+
+    /// React every mixture once, then process the hotspots and let the bypassing ones spread.
+    /// Hotspots burn their share of the mixture without rolling any chances.
+    /// Gas doesn't flow between the mixtures, only heat radiated by fires does.
+    pub fn tick(&mut self) {
+        let mut rng = self.rng;
+        let fires: Vec<f64> = self
+            .airs
+            .iter_mut()
+            .map(|air| {
+                let mut output = R::ReactionOutput::default();
+                *air = R::react_once_reporting(*air, &mut output, rng.as_mut());
+                output.fire
+            })
+            .collect();
+
+        let mut spreads = Vec::new();
+        for (index, fire) in fires.into_iter().enumerate() {
+            if let Some(hotspot) = self.hotspots[index] {
+                let (hotspot, air) = hotspot.process(self.airs[index], fire);
+                self.airs[index] = air;
+                self.hotspots[index] = hotspot;
+
+                if let Some(radiated) = hotspot.and_then(|h| h.radiated_temperature(&air)) {
+                    spreads.push((index, radiated));
+                }
+            }
+        }
+
+        for (index, radiated) in spreads {
+            for neighbour in self.adjacency[index].clone() {
+                if self.hotspots[neighbour].is_none() && rng::prob(rng.as_mut(), self.spread_chance)
+                {
+                    self.expose(neighbour, radiated, C::CELL_VOLUME / 4.);
+                }
+            }
+        }
+        self.rng = rng;
+    }
+
+    pub fn burning_count(&self) -> usize {
+        self.hotspots.iter().filter(|h| h.is_some()).count()
+    }
+}
@@ -1,8 +1,25 @@
+pub mod canister;
+pub mod conditions;
 pub mod constants;
+pub mod diagnostics;
+pub mod dmm;
+pub mod equalization;
+pub mod filters;
 pub mod gas;
 pub mod gas_mixture;
+pub mod golden;
+pub mod grid;
+pub mod hotspot;
 pub mod reactions;
+pub mod rng;
+pub mod simulation;
+pub mod snapshot;
+pub mod supermatter;
+pub mod tank;
+pub mod teg;
 pub mod tests;
+pub mod thermomachine;
+pub mod timeline;
 
 pub mod macros;
 
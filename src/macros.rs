@@ -73,20 +73,136 @@ macro_rules! temperature {
 macro_rules! reaction {
     {
         called($name:ident)
+        described_as($info:ident, $human_name:expr)
         with($($g:expr => $ma:expr),+)
+        producing($($product:expr),*)
         at($min_temp:expr)
+        $(when($($cond:ident($($arg:expr),+)),+))?
+        $(suppressed_by($suppression:expr))?
         with_gm_as($gm_name:ident) =>
         $code: tt
     } => {
-        #[inline]
-        pub fn $name($gm_name: $crate::GasMixture) -> $crate::GasMixture {
-            if (
-                $gm_name.temperature >= $min_temp &&
+        reaction! {
+            called($name)
+            described_as($info, $human_name)
+            with($($g => $ma),+)
+            producing($($product),*)
+            at($min_temp)
+            $(when($($cond($($arg),+)),+))?
+            $(suppressed_by($suppression))?
+            with_gm_as($gm_name)
+            reporting_to(_output) =>
+            $code
+        }
+    };
+    {
+        called($name:ident)
+        described_as($info:ident, $human_name:expr)
+        with($($g:expr => $ma:expr),+)
+        producing($($product:expr),*)
+        at($min_temp:expr)
+        $(when($($cond:ident($($arg:expr),+)),+))?
+        with_gm_as($gm_name:ident)
+        reporting_to($output_name:ident)
+        $(rolling_with($rng_name:ident))? =>
+        $code: tt
+    } => {
+        reaction! {
+            called($name)
+            described_as($info, $human_name)
+            with($($g => $ma),+)
+            producing($($product),*)
+            at($min_temp)
+            $(when($($cond($($arg),+)),+))?
+            suppressed_by($crate::reactions::Suppression::DEFAULT)
+            with_gm_as($gm_name)
+            reporting_to($output_name)
+            $(rolling_with($rng_name))? =>
+            $code
+        }
+    };
+    {
+        called($name:ident)
+        described_as($info:ident, $human_name:expr)
+        with($($g:expr => $ma:expr),+)
+        producing($($product:expr),*)
+        at($min_temp:expr)
+        $(when($($cond:ident($($arg:expr),+)),+))?
+        suppressed_by($suppression:expr)
+        with_gm_as($gm_name:ident)
+        reporting_to($output_name:ident) =>
+        $code: tt
+    } => {
+        reaction! {
+            called($name)
+            described_as($info, $human_name)
+            with($($g => $ma),+)
+            producing($($product),*)
+            at($min_temp)
+            $(when($($cond($($arg),+)),+))?
+            suppressed_by($suppression)
+            with_gm_as($gm_name)
+            reporting_to($output_name)
+            rolling_with(_rng) =>
+            $code
+        }
+    };
+    {
+        called($name:ident)
+        described_as($info:ident, $human_name:expr)
+        with($($g:expr => $ma:expr),+)
+        producing($($product:expr),*)
+        at($min_temp:expr)
+        $(when($($cond:ident($($arg:expr),+)),+))?
+        suppressed_by($suppression:expr)
+        with_gm_as($gm_name:ident)
+        reporting_to($output_name:ident)
+        rolling_with($rng_name:ident) =>
+        $code: tt
+    } => {
+        pub const $info: $crate::reactions::Reaction = $crate::reactions::Reaction {
+            id: stringify!($name),
+            name: $human_name,
+            conditions: &[
+                $crate::conditions::Condition::MinTemperature($min_temp),
                 $(
-                    $gm_name[$g] >= $ma
-                )&&+
-            ) {
-                $code
+                    $crate::conditions::Condition::MinMoles($g, $ma),
+                )+
+                $($(
+                    $crate::conditions::Condition::$cond($($arg),+),
+                )+)?
+            ],
+            products: &[$($product),*],
+            suppression: $suppression,
+            react: $name,
+        };
+
+        #[inline]
+        pub fn $name(
+            $gm_name: $crate::GasMixture,
+            $output_name: &mut $crate::reactions::ReactionOutput,
+            $rng_name: Option<&mut $crate::rng::Rng>
+        ) -> $crate::GasMixture {
+            if $info.conditions_hold(&$gm_name) {
+                let factor = $info.suppression.factor(&$gm_name);
+
+                if factor < 1. {
+                    $output_name.suppressed = true;
+                }
+
+                if factor <= 0. {
+                    $gm_name
+                } else {
+                    let before = *$output_name;
+                    let result = $code;
+
+                    if factor < 1. {
+                        $output_name.scale_change_since(&before, factor);
+                        $gm_name.scale_change_to(&result, factor)
+                    } else {
+                        result
+                    }
+                }
             } else {
                 $gm_name
             }
@@ -94,22 +210,6 @@ macro_rules! reaction {
     };
 }
 
-#[macro_export]
-macro_rules! chained_call {
-    (
-        $final_argument:expr => $last_func:ident
-    ) => {
-        $last_func($final_argument)
-    };
-    (
-        $starting_value:expr => $target_func:ident => $($rest:ident) => +
-    ) => {
-        chained_call! {
-            $target_func($starting_value) => $($rest) => +
-        }
-    }
-}
-
 #[macro_export]
 macro_rules! test_reaction{
     (
@@ -157,7 +257,7 @@ macro_rules! test_reaction{
                 in($vol)
             );
 
-            let result = $func(g0);
+            let result = $func(g0, &mut $crate::reactions::ReactionOutput::default(), None);
 
             assert!(
                 approx_eq!(
@@ -188,3 +288,25 @@ macro_rules! test_reaction{
         }
     }
 }
+
+#[macro_export]
+macro_rules! test_traces {
+    (
+        from($traces:expr)
+        named($($name:ident),+ $(,)?)
+    ) => {
+        $(
+            #[test]
+            fn $name() {
+                let trace = $crate::golden::parse_traces($traces)
+                    .unwrap()
+                    .into_iter()
+                    .find(|trace| trace.name == stringify!($name))
+                    .expect("There's no trace named after the test");
+                let report = trace.replay();
+
+                assert!(report.passed(), "{}", report);
+            }
+        )+
+    };
+}
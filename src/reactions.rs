@@ -1,12 +1,162 @@
+use crate::conditions::Condition;
 use crate::constants as C;
-use crate::enum_map;
 use crate::gas::*;
-use crate::{
-    chained_call, gas_mixture::*, gen_gas_mix_with_energy, gen_gas_vec, reaction, temperature,
-};
+use crate::rng::{self, Rng};
+use crate::simulation::{Stop, StopCondition};
+use crate::{gas_mixture::*, gen_gas_mix_with_energy, gen_gas_vec, reaction, temperature};
+
+/// Side effects of a reaction that do not end up in the gas mixture itself
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct ReactionOutput {
+    /// Research points the reaction would generate for the station
+    pub research: f64,
+    /// Total strength of the radiation pulses emitted by the reactions
+    pub radiation: f64,
+    /// Amount of fuel burned by fires, this is what makes hotspots grow
+    pub fire: f64,
+    /// Whether hyper-noblium stopped or slowed down a reaction that would have happened otherwise
+    pub suppressed: bool,
+    /// Readout of the fusion reaction, left at default if fusion didn't happen
+    pub fusion: FusionOutput,
+    /// Bitmask of the reactions in `REACTIONS` that changed the mixture
+    pub fired: u32,
+    /// Nuclear particles fired by fusion
+    pub particles: u32,
+}
+
+impl ReactionOutput {
+    /// Scale what was added to the readouts since `before` by `factor`, like partial suppression does to the mixture
+    pub(crate) fn scale_change_since(&mut self, before: &ReactionOutput, factor: f64) {
+        let scale = |before: f64, after: f64| before + (after - before) * factor;
+        self.research = scale(before.research, self.research);
+        self.radiation = scale(before.radiation, self.radiation);
+        self.fire = scale(before.fire, self.fire);
+        self.fusion.reaction_energy =
+            scale(before.fusion.reaction_energy, self.fusion.reaction_energy);
+        self.fusion.radiation = scale(before.fusion.radiation, self.fusion.radiation);
+    }
+
+    /// Reactions that changed the mixture, in the order they're attempted in
+    pub fn fired_reactions(&self) -> impl Iterator<Item = &'static Reaction> + '_ {
+        REACTIONS
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| self.fired & (1 << index) != 0)
+            .map(|(_, reaction)| reaction)
+    }
+}
+
+/// Whether a reaction consumed or released energy
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(u8)]
+pub enum Thermality {
+    #[default]
+    Neutral,
+    Endothermic,
+    Exothermic,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct FusionOutput {
+    pub happened: bool,
+    pub instability: f64,
+    pub toroidal_size: f64,
+    pub reaction_energy: f64,
+    pub thermality: Thermality,
+    /// Strength of the radiation pulse emitted by the reaction
+    pub radiation: f64,
+    /// Chance from 0 to 1 that the reaction fires a nuclear particle
+    pub particle_chance: f64,
+}
+
+/// How hyper-noblium affects a reaction
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Suppression {
+    /// The reaction ignores noblium altogether
+    Immune,
+    /// The reaction stops once there's at least `threshold` moles of noblium
+    Full { threshold: f64 },
+    /// The changes the reaction makes to the mixture are scaled by `factor` once there's at least `threshold` moles of noblium
+    Partial { threshold: f64, factor: f64 },
+}
+
+impl Default for Suppression {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Suppression {
+    /// Reactions stop once there's enough noblium, like they do in the game
+    pub const DEFAULT: Self = Suppression::Full {
+        threshold: C::REACTION_OPPRESSION_THRESHOLD,
+    };
+
+    /// How much of the reaction still takes place in `gm`, from 0 to 1
+    pub fn factor(&self, gm: &GasMixture) -> f64 {
+        match *self {
+            Suppression::Immune => 1.,
+            Suppression::Full { threshold } if gm[Gas::HNb] >= threshold => 0.,
+            Suppression::Partial { threshold, factor } if gm[Gas::HNb] >= threshold => factor,
+            _ => 1.,
+        }
+    }
+}
+
+/// A reaction along with everything known about it ahead of running it
+#[derive(Copy, Clone, Debug)]
+pub struct Reaction {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Everything the mixture has to meet for the reaction to happen
+    pub conditions: &'static [Condition],
+    pub products: &'static [Gas],
+    /// Only set by `reaction!`, the generated `react` reads the suppression of the const and not of copies of it
+    pub(crate) suppression: Suppression,
+    pub react: fn(GasMixture, &mut ReactionOutput, Option<&mut Rng>) -> GasMixture,
+}
+
+impl Reaction {
+    /// How hyper-noblium affects the reaction
+    pub fn suppression(&self) -> Suppression {
+        self.suppression
+    }
+
+    pub fn conditions_hold(&self, gm: &GasMixture) -> bool {
+        self.conditions.iter().all(|condition| condition.holds(gm))
+    }
+
+    /// Gases the reaction needs along with their minimum amounts
+    pub fn required_gases(&self) -> impl Iterator<Item = (Gas, f64)> {
+        self.conditions
+            .iter()
+            .filter_map(|condition| match *condition {
+                Condition::MinMoles(gas, amount) => Some((gas, amount)),
+                _ => None,
+            })
+    }
+
+    pub fn min_temperature(&self) -> f64 {
+        self.conditions
+            .iter()
+            .filter_map(|condition| match *condition {
+                Condition::MinTemperature(t) => Some(t),
+                _ => None,
+            })
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
 
-fn verify_hnob(gm: &GasMixture) -> bool {
-    gm[Gas::HNb] < 5.0
+    pub fn max_temperature(&self) -> f64 {
+        self.conditions
+            .iter()
+            .filter_map(|condition| match *condition {
+                Condition::MaxTemperature(t) => Some(t),
+                _ => None,
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
 }
 
 pub fn atmos_mod(lhs: f64, rhs: f64) -> f64 {
@@ -15,9 +165,11 @@ pub fn atmos_mod(lhs: f64, rhs: f64) -> f64 {
 
 reaction! (
     called(n2o_decomp)
+    described_as(N2O_DECOMP, "Nitrous Oxide Decomposition")
     with(
         Gas::N2O => C::MINIMUM_MOLE_COUNT
     )
+    producing(Gas::O2, Gas::N2)
     at(temperature!(C::N2O_DECOMPOSITION_MIN_ENERGY, K))
     with_gm_as(gm) => {
         let n2o = gm[Gas::N2O];
@@ -41,12 +193,15 @@ reaction! (
 
 reaction! (
     called(plasma_fire)
+    described_as(PLASMA_FIRE, "Plasma Combustion")
     with(
         Gas::Pl => C::MINIMUM_MOLE_COUNT,
         Gas::O2 => C::MINIMUM_MOLE_COUNT
     )
+    producing(Gas::CO2, Gas::H2)
     at(temperature!(C::PLASMA_MINIMUM_BURN_TEMPERATURE, K))
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output) => {
         let pl = gm[Gas::Pl];
         let o2 = gm[Gas::O2];
         let t = gm.temperature;
@@ -70,6 +225,8 @@ reaction! (
         let is_satured = o2 / pl > C::SUPER_SATURATION_THRESHOLD;
         let energy_release = plasma_burn_rate * C::FIRE_PLASMA_ENERGY_RELEASED;
 
+        output.fire += plasma_burn_rate * (1. + oxygen_burn_rate);
+
         gm + gen_gas_mix_with_energy!(
             with (
                 Gas::Pl => -plasma_burn_rate,
@@ -84,12 +241,16 @@ reaction! (
 
 reaction! (
     called(trit_fire)
+    described_as(TRIT_FIRE, "Tritium Combustion")
     with(
         Gas::H2 => C::MINIMUM_MOLE_COUNT,
         Gas::O2 => C::MINIMUM_MOLE_COUNT
     )
+    producing(Gas::H2O)
     at(temperature!(100.0, C))
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output)
+    rolling_with(rng) => {
         let e = gm.get_energy();
         let h2 = gm[Gas::H2];
         let o2 = gm[Gas::O2];
@@ -100,6 +261,13 @@ reaction! (
         let extra_energy_release = if !o2_no_combust {primary_energy_release * (C::TRITIUM_BURN_TRIT_FACTOR - 1.)} else {0.};
         let energy_release = extra_energy_release + primary_energy_release;
 
+        output.fire += burned_fuel;
+
+        // The game only pulses on some of the burns
+        if burned_fuel > C::TRITIUM_MINIMUM_RADIATION_ENERGY && rng::prob(rng, C::TRITIUM_RADIATION_CHANCE) {
+            output.radiation += energy_release / C::TRITIUM_BURN_RADIOACTIVITY_FACTOR;
+        }
+
         gm + gen_gas_mix_with_energy!(
             with(
                 Gas::H2O => burned_fuel,
@@ -114,13 +282,17 @@ reaction! (
 
 reaction! (
     called(fusion)
+    described_as(FUSION, "Plasmic Fusion")
     with(
         Gas::H2 => C::FUSION_TRITIUM_MOLES_USED,
         Gas::Pl => C::FUSION_MOLE_THRESHOLD,
         Gas::CO2 => C::FUSION_MOLE_THRESHOLD
     )
+    producing(Gas::H2O, Gas::BZ, Gas::O2)
     at(temperature!(C::FUSION_TEMPERATURE_THRESHOLD, K))
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output)
+    rolling_with(rng) => {
         let e = gm.get_energy();
         let pl = gm.gases[Gas::Pl];
         let co2 = gm.gases[Gas::CO2];
@@ -195,20 +367,91 @@ reaction! (
         );
 
         if reaction_energy != 0.0 || instability <= C::FUSION_INSTABILITY_ENDOTHERMALITY {
-            gm + delta_mix
+            let result = gm + delta_mix;
+
+            let (radiation, particle_chance) = if reaction_energy != 0.0 {
+                // Waste gases don't count towards the radiation, only the plasma does
+                let standard_energy = 400. * result[Gas::Pl] * result.temperature;
+                let radiation = (2000. * 3_f64.powf(standard_energy.log10() - C::FUSION_RAD_MIDPOINT)).max(0.);
+                let particle_chance = C::PARTICLE_CHANCE_CONSTANT / (reaction_energy - C::PARTICLE_CHANCE_CONSTANT) + 1.;
+
+                (radiation, particle_chance.clamp(0., 1.))
+            } else {
+                (0., 0.)
+            };
+
+            output.radiation += radiation;
+            output.fusion = FusionOutput {
+                happened: true,
+                instability,
+                toroidal_size,
+                reaction_energy,
+                thermality: if reaction_energy > 0. {
+                    Thermality::Exothermic
+                } else if reaction_energy < 0. {
+                    Thermality::Endothermic
+                } else {
+                    Thermality::Neutral
+                },
+                radiation,
+                particle_chance,
+            };
+            if rng::prob(rng, particle_chance * 100.) {
+                output.particles += 1;
+            }
+
+            result
         } else {
             gm
         }
     }
 );
 
+reaction! (
+    called(pluox_formation)
+    described_as(PLUOX_FORMATION, "Pluoxium Formation")
+    with(
+        Gas::CO2 => C::MINIMUM_MOLE_COUNT,
+        Gas::O2 => C::MINIMUM_MOLE_COUNT,
+        Gas::H2 => C::MINIMUM_MOLE_COUNT
+    )
+    producing(Gas::PlOx)
+    at(temperature!(C::PLUOXIUM_FORMATION_MIN_TEMP, K))
+    when(
+        MaxTemperature(temperature!(C::PLUOXIUM_FORMATION_MAX_TEMP, K))
+    )
+    with_gm_as(gm) => {
+        let co2 = gm[Gas::CO2];
+        let o2 = gm[Gas::O2];
+        let h2 = gm[Gas::H2];
+
+        let produced_amount = C::PLUOXIUM_MAX_PRODUCTION.min(co2).min(o2);
+
+        if h2 < produced_amount * 0.01 {
+            gm
+        } else {
+            gm + gen_gas_mix_with_energy!(
+                with(
+                    Gas::CO2 => -produced_amount,
+                    Gas::O2 => -produced_amount * 0.5,
+                    Gas::H2 => -produced_amount * 0.01,
+                    Gas::PlOx => produced_amount,
+                )
+                at(produced_amount * C::PLUOXIUM_FORMATION_ENERGY)
+            )
+        }
+    }
+);
+
 reaction! (
     called(nitryl_formation)
+    described_as(NITRYL_FORMATION, "Nitryl Formation")
     with(
         Gas::N2 => 20.,
         Gas::O2 => 20.,
         Gas::PlOx => 5.
     )
+    producing(Gas::NO2)
     at(temperature!(C::FIRE_MINIMUM_TEMPERATURE_TO_EXIST * 60., K))
     with_gm_as(gm) => {
         let n2 = gm[Gas::N2];
@@ -232,12 +475,15 @@ reaction! (
 
 reaction! (
     called(bz_synth)
+    described_as(BZ_SYNTH, "BZ Gas Formation")
     with(
         Gas::N2O => 10.,
         Gas::Pl => 10.
     )
+    producing(Gas::BZ, Gas::O2)
     at(f64::NEG_INFINITY)
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output) => {
         let p = gm.get_pressure();
         let pl = gm[Gas::Pl];
         let n2o = gm[Gas::N2O];
@@ -254,6 +500,8 @@ reaction! (
 
         let bz_prod = usage - p.max(1.);
 
+        output.research += (usage.powi(2) * C::BZ_RESEARCH_SCALE).min(C::BZ_RESEARCH_MAX_AMOUNT);
+
         gm + gen_gas_mix_with_energy!(
             with(
                 Gas::N2O => -usage,
@@ -268,14 +516,17 @@ reaction! (
 
 reaction! (
     called(stimulum_synth)
+    described_as(STIMULUM_SYNTH, "Stimulum Formation")
     with(
         Gas::H2 => 30.,
         Gas::Pl => 10.,
         Gas::BZ => 20.,
         Gas::NO2 => 30.
     )
+    producing(Gas::ST)
     at(C::STIMULUM_HEAT_SCALE / 2.)
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output) => {
         const COEFFS: [f64; 5] = [1., C::STIMULUM_FIRST_RISE, -C::STIMULUM_FIRST_DROP, C::STIMULUM_SECOND_RISE, -C::STIMULUM_ABSOLUTE_DROP];
 
         let t = gm.temperature;
@@ -286,6 +537,8 @@ reaction! (
         let heat_scale = (t / C::STIMULUM_HEAT_SCALE).min(pl).min(no2).min(h2);
         let energy_delta = (1..5).zip(COEFFS.iter()).map(|(i, c)| c * heat_scale.powi(i)).sum::<f64>();
 
+        output.research += C::STIMULUM_RESEARCH_AMOUNT * energy_delta.max(0.);
+
         gm + gen_gas_mix_with_energy!(
             with(
                 Gas::ST => heat_scale / 10.,
@@ -300,12 +553,15 @@ reaction! (
 
 reaction! (
     called(hnob_synth)
+    described_as(HNOB_SYNTH, "Hyper-Noblium Condensation")
     with(
         Gas::N2 => 10.,
         Gas::H2 => 5.
     )
+    producing(Gas::HNb)
     at(5e6)
-    with_gm_as(gm) => {
+    with_gm_as(gm)
+    reporting_to(output) => {
         let n2 = gm[Gas::N2];
         let h2 = gm[Gas::H2];
         let bz = gm[Gas::BZ];
@@ -313,6 +569,8 @@ reaction! (
         let nob_formed = (0.01 * (n2 + h2)).min(h2 / 10.).min(n2 / 20.);
         let energy_used = nob_formed * C::NOBLIUM_FORMATION_ENERGY / bz.max(1.);
 
+        output.research += nob_formed * C::NOBLIUM_RESEARCH_AMOUNT;
+
         gm + gen_gas_mix_with_energy!(
             with(
                 Gas::H2 => -10. * nob_formed,
@@ -324,22 +582,93 @@ reaction! (
     }
 );
 
-pub fn react_once(gm: GasMixture) -> GasMixture {
-    if verify_hnob(&gm) {
-        chained_call! (
-            gm =>
-            n2o_decomp =>
-            trit_fire =>
-            plasma_fire =>
-            fusion =>
-            nitryl_formation =>
-            bz_synth =>
-            stimulum_synth =>
-            hnob_synth
-        )
-    } else {
-        gm
+reaction! (
+    called(miasma_sterilization)
+    described_as(MIASMA_STERILIZATION, "Dry Heat Sterilization")
+    with(
+        Gas::Miasma => C::MINIMUM_MOLE_COUNT
+    )
+    producing(Gas::O2)
+    at(temperature!(C::FIRE_MINIMUM_TEMPERATURE_TO_EXIST + 70., K))
+    when(
+        // Too much water vapor stops the sterilization altogether
+        MaxRatio(Gas::H2O, 0.1)
+    )
+    with_gm_as(gm)
+    reporting_to(output) => {
+        let miasma = gm[Gas::Miasma];
+        let t = gm.temperature;
+
+        let cleaned_air = miasma.min(20. + (t - C::FIRE_MINIMUM_TEMPERATURE_TO_EXIST - 70.) / 20.);
+
+        output.research += cleaned_air * C::MIASMA_RESEARCH_AMOUNT;
+
+        // Miasma and oxygen have the same heat capacity, the slight heating is applied to the temperature directly
+        GasMixture {
+            gases: gm.gases + gen_gas_vec!(
+                Gas::Miasma => -cleaned_air,
+                Gas::O2 => cleaned_air,
+            ),
+            temperature: t + cleaned_air * 0.002,
+            ..gm
+        }
     }
+);
+
+/// Every reaction in the order they're attempted in
+pub const REACTIONS: &[Reaction] = &[
+    N2O_DECOMP,
+    TRIT_FIRE,
+    PLASMA_FIRE,
+    FUSION,
+    PLUOX_FORMATION,
+    NITRYL_FORMATION,
+    BZ_SYNTH,
+    STIMULUM_SYNTH,
+    HNOB_SYNTH,
+    MIASMA_STERILIZATION,
+];
+
+/// React `gm` once, adding the side effects to `output`.
+/// Chances are rolled with `rng`, without one every roll that could pass does.
+pub fn react_once_reporting(
+    gm: GasMixture,
+    output: &mut ReactionOutput,
+    mut rng: Option<&mut Rng>,
+) -> GasMixture {
+    REACTIONS
+        .iter()
+        .enumerate()
+        .fold(gm, |gm, (index, reaction)| {
+            let result = (reaction.react)(gm, output, rng.as_deref_mut());
+            if result != gm {
+                output.fired |= 1 << index;
+            }
+
+            result
+        })
+}
+
+/// React `gm` once without a generator, so every chance gate passes as long as its chance is above 0.
+/// The radiation and particles reported are what the luckiest roll would give, not what to expect on average.
+pub fn react_once_with_output(gm: GasMixture) -> (GasMixture, ReactionOutput) {
+    let mut output = ReactionOutput::default();
+    let result = react_once_reporting(gm, &mut output, None);
+
+    (result, output)
+}
+
+/// React `gm` once, rolling every chance with `rng`
+pub fn react_once_rolling(gm: GasMixture, rng: &mut Rng) -> (GasMixture, ReactionOutput) {
+    let mut output = ReactionOutput::default();
+    let result = react_once_reporting(gm, &mut output, Some(rng));
+
+    (result, output)
+}
+
+/// React `gm` once, passing every chance gate like `react_once_with_output` does
+pub fn react_once(gm: GasMixture) -> GasMixture {
+    react_once_with_output(gm).0
 }
 
 pub fn react_several(gm: GasMixture, times: usize) -> Vec<GasMixture> {
@@ -353,6 +682,37 @@ pub fn react_several(gm: GasMixture, times: usize) -> Vec<GasMixture> {
     result
 }
 
+/// React `gm` up to `times` times, stopping right after the step where any of `stop_conditions` holds.
+/// The intermediate states are returned along with what stopped the run, if anything did.
+pub fn react_several_until(
+    gm: GasMixture,
+    times: usize,
+    stop_conditions: &[StopCondition],
+) -> (Vec<GasMixture>, Option<Stop>) {
+    let mut result = Vec::with_capacity(times);
+    let mut cur = gm;
+    for step in 1..=times {
+        let prev = cur;
+        cur = react_once(cur);
+        result.push(cur);
+
+        if let Some(condition) = stop_conditions
+            .iter()
+            .find(|condition| condition.holds(&prev, &cur))
+        {
+            return (
+                result,
+                Some(Stop {
+                    condition: *condition,
+                    step,
+                }),
+            );
+        }
+    }
+
+    (result, None)
+}
+
 pub fn react_until_done(gm: GasMixture) -> GasMixture {
     let mut prev_gm = gm;
     let mut next_gm = react_once(gm);
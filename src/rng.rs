@@ -0,0 +1,41 @@
+/// A small seedable random number generator, splitmix64.
+/// The same seed always gives the same rolls, on every platform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rng {
+    /// Everything the generator remembers, saving and restoring it resumes the exact same sequence
+    pub state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including 1
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Like DM's `prob()`, passes `percent` percent of the time
+    pub fn prob(&mut self, percent: f64) -> bool {
+        self.next_f64() * 100. < percent
+    }
+}
+
+/// Roll `prob(percent)` with `rng`. Every roll with a chance passes without a generator,
+/// so runs without randomness report every side effect that could have happened.
+pub fn prob(rng: Option<&mut Rng>, percent: f64) -> bool {
+    match rng {
+        Some(rng) => rng.prob(percent),
+        None => percent > 0.,
+    }
+}
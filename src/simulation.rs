@@ -0,0 +1,104 @@
+use crate::constants as C;
+use crate::reactions::{self as R, ReactionOutput};
+use crate::rng::Rng;
+use crate::{Gas, GasMixture};
+
+/// A predicate that ends a reaction run early
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StopCondition {
+    PressureAbove(f64),
+    TemperatureAbove(f64),
+    TemperatureBelow(f64),
+    /// There's less than `MINIMUM_MOLE_COUNT` of the gas left
+    GasDepleted(Gas),
+    /// Neither the temperature nor the amount of any gas changed by more than the tolerance
+    Converged(f64),
+}
+
+impl StopCondition {
+    /// Whether the run should stop after reacting `previous` into `current`
+    pub fn holds(&self, previous: &GasMixture, current: &GasMixture) -> bool {
+        match *self {
+            StopCondition::PressureAbove(pressure) => current.get_pressure() > pressure,
+            StopCondition::TemperatureAbove(temperature) => current.temperature > temperature,
+            StopCondition::TemperatureBelow(temperature) => current.temperature < temperature,
+            StopCondition::GasDepleted(gas) => current[gas] < C::MINIMUM_MOLE_COUNT,
+            StopCondition::Converged(tolerance) => {
+                (current.temperature - previous.temperature).abs() <= tolerance
+                    && (current.gases - previous.gases)
+                        .0
+                        .values()
+                        .all(|delta| delta.abs() <= tolerance)
+            }
+        }
+    }
+}
+
+/// The condition that ended a run and the step it held on, counting from 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stop {
+    pub condition: StopCondition,
+    pub step: usize,
+}
+
+/// A mixture being reacted step by step, which can be paused and resumed at any point
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Simulation {
+    pub mixture: GasMixture,
+    /// Amount of steps taken so far
+    pub steps: usize,
+    /// Side effects of the latest step
+    pub output: ReactionOutput,
+    /// Generator the chances of the reactions are rolled with, every roll passes without one
+    pub rng: Option<Rng>,
+}
+
+impl Simulation {
+    pub fn new(mixture: GasMixture) -> Self {
+        Self {
+            mixture,
+            steps: 0,
+            output: ReactionOutput::default(),
+            rng: None,
+        }
+    }
+
+    /// A simulation rolling its chances with a generator seeded with `seed`, the same seed always gives the same run
+    pub fn seeded(mixture: GasMixture, seed: u64) -> Self {
+        Self {
+            rng: Some(Rng::new(seed)),
+            ..Self::new(mixture)
+        }
+    }
+
+    pub fn step(&mut self) -> GasMixture {
+        let mut output = ReactionOutput::default();
+        let mixture = R::react_once_reporting(self.mixture, &mut output, self.rng.as_mut());
+        self.mixture = mixture;
+        self.output = output;
+        self.steps += 1;
+
+        mixture
+    }
+
+    /// React up to `times` times, stopping right after the step where any of `stop_conditions` holds.
+    /// Returns what stopped the run, with the step counted from the creation of the simulation.
+    pub fn run(&mut self, times: usize, stop_conditions: &[StopCondition]) -> Option<Stop> {
+        for _ in 1..=times {
+            let previous = self.mixture;
+            let current = self.step();
+
+            if let Some(condition) = stop_conditions
+                .iter()
+                .find(|condition| condition.holds(&previous, &current))
+            {
+                return Some(Stop {
+                    condition: *condition,
+                    step: self.steps,
+                });
+            }
+        }
+
+        None
+    }
+}
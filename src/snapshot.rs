@@ -0,0 +1,829 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::canister::Canister;
+use crate::filters::{Filter, Scrubber, ScrubberMode};
+use crate::gas::{GasEnumMap, GasFilter};
+use crate::grid::{ExcitedGroup, Grid, Turf};
+use crate::reactions::{FusionOutput, ReactionOutput, Thermality};
+use crate::rng::Rng;
+use crate::simulation::Simulation;
+use crate::supermatter::Supermatter;
+use crate::tank::Tank;
+use crate::teg::Teg;
+use crate::thermomachine::{HeatExchanger, Thermomachine, ThermomachineMode};
+use crate::{gen_gas_vec, Gas, GasMixture};
+
+const MAGIC: &[u8; 4] = b"TGAS";
+/// Version of the format written by `Snapshot::to_bytes`, bumped whenever the layout changes
+pub const SNAPSHOT_VERSION: u32 = 3;
+/// Oldest version `Snapshot::from_bytes` still reads
+const OLDEST_SNAPSHOT_VERSION: u32 = 1;
+
+/// Why a snapshot couldn't be read
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    Io(io::ErrorKind),
+    /// The data doesn't start like a snapshot does
+    NotASnapshot,
+    UnsupportedVersion(u32),
+    /// The data ended in the middle of the snapshot
+    Truncated,
+    UnknownGas(String),
+    /// A value that doesn't mean anything where it was found, at the given byte offset
+    Invalid(usize),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(kind) => write!(f, "couldn't read the snapshot: {:?}", kind),
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Truncated => write!(f, "truncated snapshot"),
+            SnapshotError::UnknownGas(id) => write!(f, "unknown gas \"{}\"", id),
+            SnapshotError::Invalid(offset) => write!(f, "invalid value at byte {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error.kind())
+    }
+}
+
+/// A machine and its settings
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Machine {
+    Canister(Canister),
+    Scrubber(Scrubber),
+    Filter(Filter),
+    Thermomachine(Thermomachine),
+    HeatExchanger(HeatExchanger),
+    Teg(Teg),
+    Supermatter(Supermatter),
+    Tank(Tank),
+}
+
+/// A machine and what it's connected to
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacedMachine {
+    pub machine: Machine,
+    /// Turf of the grid the machine is on
+    pub turf: Option<usize>,
+    /// Indices of the pipenets the machine is connected to, in the order of its ports
+    pub pipenets: Vec<usize>,
+}
+
+/// Where two snapshots differ, with both values written out
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    /// Where in the snapshot, like `pipenets[2].temperature`
+    pub path: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// The whole state of an experiment, which can be saved, restored and resumed exactly where it left off.
+/// Floats are stored bit for bit, so a restored state behaves exactly like the original.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub grid: Option<Grid>,
+    pub pipenets: Vec<GasMixture>,
+    pub machines: Vec<PlacedMachine>,
+    pub simulations: Vec<Simulation>,
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn mixture(&mut self, mixture: &GasMixture) {
+        mixture
+            .gases
+            .0
+            .values()
+            .for_each(|&amount| self.f64(amount));
+        self.f64(mixture.temperature);
+        self.f64(mixture.volume);
+    }
+
+    fn filter(&mut self, filter: &GasFilter) {
+        filter.values().for_each(|&filtered| self.bool(filtered));
+    }
+
+    fn grid(&mut self, grid: &Grid) {
+        self.usize(grid.width);
+        self.usize(grid.height);
+        for turf in &grid.turfs {
+            match turf {
+                Turf::Closed => self.u8(0),
+                Turf::Open(air) => {
+                    self.u8(1);
+                    self.mixture(air);
+                }
+                Turf::Space => self.u8(2),
+                Turf::Planetary { air, atmosphere } => {
+                    self.u8(3);
+                    self.mixture(air);
+                    self.mixture(atmosphere);
+                }
+                Turf::Immutable(air) => {
+                    self.u8(4);
+                    self.mixture(air);
+                }
+            }
+        }
+        (0..grid.turfs.len()).for_each(|index| self.bool(grid.active.contains(&index)));
+        self.usize(grid.excited_groups.len());
+        for group in &grid.excited_groups {
+            self.usize(group.turfs.len());
+            group.turfs.iter().for_each(|&turf| self.usize(turf));
+            self.u32(group.breakdown_cooldown);
+            self.u32(group.dismantle_cooldown);
+        }
+        self.bool(grid.space_is_all_consuming);
+    }
+
+    fn machine(&mut self, machine: &Machine) {
+        match machine {
+            Machine::Canister(canister) => {
+                self.u8(0);
+                self.mixture(&canister.air);
+                self.f64(canister.maximum_pressure);
+                self.f64(canister.release_pressure);
+                self.bool(canister.valve_open);
+                self.bool(canister.holding.is_some());
+                if let Some(holding) = &canister.holding {
+                    self.mixture(holding);
+                }
+                self.bool(canister.ruptured);
+            }
+            Machine::Scrubber(scrubber) => {
+                self.u8(1);
+                self.u8(match scrubber.mode {
+                    ScrubberMode::Scrubbing => 0,
+                    ScrubberMode::Siphoning => 1,
+                });
+                self.f64(scrubber.volume_rate);
+                self.filter(&scrubber.filter);
+            }
+            Machine::Filter(filter) => {
+                self.u8(2);
+                self.f64(filter.transfer_rate);
+                self.filter(&filter.filter);
+            }
+            Machine::Thermomachine(thermomachine) => {
+                self.u8(3);
+                self.u8(match thermomachine.mode {
+                    ThermomachineMode::Freezer => 0,
+                    ThermomachineMode::Heater => 1,
+                });
+                self.f64(thermomachine.target_temperature);
+                self.f64(thermomachine.heat_capacity);
+                self.f64(thermomachine.min_temperature);
+                self.f64(thermomachine.max_temperature);
+            }
+            Machine::HeatExchanger(exchanger) => {
+                self.u8(4);
+                self.f64(exchanger.efficiency);
+            }
+            Machine::Teg(teg) => {
+                self.u8(5);
+                self.f64(teg.efficiency);
+            }
+            Machine::Supermatter(supermatter) => {
+                self.u8(6);
+                self.f64(supermatter.power);
+                self.f64(supermatter.damage);
+                self.f64(supermatter.matter_power);
+                self.f64(supermatter.powerloss_dynamic_scaling);
+                self.f64(supermatter.powerloss_inhibitor);
+                self.f64(supermatter.mole_heat_penalty);
+                self.f64(supermatter.dynamic_heat_resistance);
+                self.f64(supermatter.combined_gas);
+            }
+            Machine::Tank(tank) => {
+                self.u8(7);
+                self.mixture(&tank.air);
+                self.u32(tank.integrity);
+            }
+        }
+    }
+
+    fn placed_machine(&mut self, placed: &PlacedMachine) {
+        self.machine(&placed.machine);
+        self.bool(placed.turf.is_some());
+        self.usize(placed.turf.unwrap_or_default());
+        self.usize(placed.pipenets.len());
+        placed
+            .pipenets
+            .iter()
+            .for_each(|&pipenet| self.usize(pipenet));
+    }
+
+    fn simulation(&mut self, simulation: &Simulation) {
+        let output = &simulation.output;
+        let fusion = &output.fusion;
+
+        self.mixture(&simulation.mixture);
+        self.usize(simulation.steps);
+        self.f64(output.research);
+        self.f64(output.radiation);
+        self.f64(output.fire);
+        self.bool(output.suppressed);
+        self.bool(fusion.happened);
+        self.f64(fusion.instability);
+        self.f64(fusion.toroidal_size);
+        self.f64(fusion.reaction_energy);
+        self.u8(fusion.thermality as u8);
+        self.f64(fusion.radiation);
+        self.f64(fusion.particle_chance);
+        self.u32(output.fired);
+        self.u32(output.particles);
+        self.bool(simulation.rng.is_some());
+        if let Some(rng) = simulation.rng {
+            self.u64(rng.state);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    version: u32,
+    /// Gases in the order the snapshot stores them in
+    gases: Vec<Gas>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, amount: usize) -> Result<&'a [u8], SnapshotError> {
+        let end = self
+            .pos
+            .checked_add(amount)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(SnapshotError::Truncated)?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid(self.pos - 1)),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, SnapshotError> {
+        self.u64()?
+            .try_into()
+            .map_err(|_| SnapshotError::Invalid(self.pos - 8))
+    }
+
+    /// A length of something at least `item_size` bytes long per item, checked against what's left of the data
+    fn len(&mut self, item_size: usize) -> Result<usize, SnapshotError> {
+        let len = self.usize()?;
+        if len.saturating_mul(item_size) > self.bytes.len() - self.pos {
+            return Err(SnapshotError::Truncated);
+        }
+
+        Ok(len)
+    }
+
+    fn f64(&mut self) -> Result<f64, SnapshotError> {
+        Ok(f64::from_bits(u64::from_le_bytes(
+            self.take(8)?.try_into().unwrap(),
+        )))
+    }
+
+    fn str(&mut self) -> Result<&'a str, SnapshotError> {
+        let len = self.len(1)?;
+        let start = self.pos;
+        std::str::from_utf8(self.take(len)?).map_err(|_| SnapshotError::Invalid(start))
+    }
+
+    fn mixture(&mut self) -> Result<GasMixture, SnapshotError> {
+        let mut gases = gen_gas_vec!();
+        for i in 0..self.gases.len() {
+            gases.0[self.gases[i]] = self.f64()?;
+        }
+
+        Ok(GasMixture {
+            gases,
+            temperature: self.f64()?,
+            volume: self.f64()?,
+        })
+    }
+
+    fn filter(&mut self) -> Result<GasFilter, SnapshotError> {
+        let mut filter = GasFilter::default();
+        for i in 0..self.gases.len() {
+            filter[self.gases[i]] = self.bool()?;
+        }
+
+        Ok(filter)
+    }
+
+    fn grid(&mut self) -> Result<Grid, SnapshotError> {
+        let width = self.usize()?;
+        let height = self.usize()?;
+        let size = width
+            .checked_mul(height)
+            .ok_or(SnapshotError::Invalid(self.pos - 8))?;
+        if size > self.bytes.len() - self.pos {
+            return Err(SnapshotError::Truncated);
+        }
+
+        let mut grid = Grid::new(width, height, Turf::Closed);
+        for turf in grid.turfs.iter_mut() {
+            *turf = match self.u8()? {
+                0 => Turf::Closed,
+                1 => Turf::Open(self.mixture()?),
+                2 => Turf::Space,
+                3 => Turf::Planetary {
+                    air: self.mixture()?,
+                    atmosphere: self.mixture()?,
+                },
+                4 => Turf::Immutable(self.mixture()?),
+                _ => return Err(SnapshotError::Invalid(self.pos - 1)),
+            };
+        }
+        grid.active.clear();
+        for index in 0..size {
+            if self.bool()? {
+                grid.active.insert(index);
+            }
+        }
+        for _ in 0..self.len(16)? {
+            let turfs = (0..self.len(8)?)
+                .map(|_| {
+                    let turf = self.usize()?;
+                    if turf < size {
+                        Ok(turf)
+                    } else {
+                        Err(SnapshotError::Invalid(self.pos - 8))
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            grid.excited_groups.push(ExcitedGroup {
+                turfs,
+                breakdown_cooldown: self.u32()?,
+                dismantle_cooldown: self.u32()?,
+            });
+        }
+        grid.index_groups();
+        // Version 2 didn't know about all-consuming space
+        grid.space_is_all_consuming = self.version >= 3 && self.bool()?;
+
+        Ok(grid)
+    }
+
+    fn machine(&mut self) -> Result<Machine, SnapshotError> {
+        Ok(match self.u8()? {
+            0 => Machine::Canister(Canister {
+                air: self.mixture()?,
+                maximum_pressure: self.f64()?,
+                release_pressure: self.f64()?,
+                valve_open: self.bool()?,
+                holding: if self.bool()? {
+                    Some(self.mixture()?)
+                } else {
+                    None
+                },
+                ruptured: self.bool()?,
+            }),
+            1 => Machine::Scrubber(Scrubber {
+                mode: match self.u8()? {
+                    0 => ScrubberMode::Scrubbing,
+                    1 => ScrubberMode::Siphoning,
+                    _ => return Err(SnapshotError::Invalid(self.pos - 1)),
+                },
+                volume_rate: self.f64()?,
+                filter: self.filter()?,
+            }),
+            2 => Machine::Filter(Filter {
+                transfer_rate: self.f64()?,
+                filter: self.filter()?,
+            }),
+            3 => Machine::Thermomachine(Thermomachine {
+                mode: match self.u8()? {
+                    0 => ThermomachineMode::Freezer,
+                    1 => ThermomachineMode::Heater,
+                    _ => return Err(SnapshotError::Invalid(self.pos - 1)),
+                },
+                target_temperature: self.f64()?,
+                heat_capacity: self.f64()?,
+                min_temperature: self.f64()?,
+                max_temperature: self.f64()?,
+            }),
+            4 => Machine::HeatExchanger(HeatExchanger {
+                efficiency: self.f64()?,
+            }),
+            5 => Machine::Teg(Teg {
+                efficiency: self.f64()?,
+            }),
+            6 => Machine::Supermatter(Supermatter {
+                power: self.f64()?,
+                damage: self.f64()?,
+                matter_power: self.f64()?,
+                powerloss_dynamic_scaling: self.f64()?,
+                powerloss_inhibitor: self.f64()?,
+                mole_heat_penalty: self.f64()?,
+                dynamic_heat_resistance: self.f64()?,
+                combined_gas: self.f64()?,
+            }),
+            7 => Machine::Tank(Tank {
+                air: self.mixture()?,
+                integrity: self.u32()?,
+            }),
+            _ => return Err(SnapshotError::Invalid(self.pos - 1)),
+        })
+    }
+
+    fn placed_machine(&mut self) -> Result<PlacedMachine, SnapshotError> {
+        let machine = self.machine()?;
+        let on_turf = self.bool()?;
+        let turf = self.usize()?;
+
+        Ok(PlacedMachine {
+            machine,
+            turf: on_turf.then_some(turf),
+            pipenets: (0..self.len(8)?)
+                .map(|_| self.usize())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn simulation(&mut self) -> Result<Simulation, SnapshotError> {
+        Ok(Simulation {
+            mixture: self.mixture()?,
+            steps: self.usize()?,
+            output: ReactionOutput {
+                research: self.f64()?,
+                radiation: self.f64()?,
+                fire: self.f64()?,
+                suppressed: self.bool()?,
+                fusion: FusionOutput {
+                    happened: self.bool()?,
+                    instability: self.f64()?,
+                    toroidal_size: self.f64()?,
+                    reaction_energy: self.f64()?,
+                    thermality: match self.u8()? {
+                        0 => Thermality::Neutral,
+                        1 => Thermality::Endothermic,
+                        2 => Thermality::Exothermic,
+                        _ => return Err(SnapshotError::Invalid(self.pos - 1)),
+                    },
+                    radiation: self.f64()?,
+                    particle_chance: self.f64()?,
+                },
+                fired: self.u32()?,
+                // Version 1 didn't know about particles or random number generators
+                particles: if self.version >= 2 { self.u32()? } else { 0 },
+            },
+            rng: if self.version >= 2 && self.bool()? {
+                Some(Rng { state: self.u64()? })
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Values are compared by their debug text, which tells apart floats that differ in any bit but the payload of a NaN
+fn diff_value<T: fmt::Debug>(
+    differences: &mut Vec<Difference>,
+    path: String,
+    before: &T,
+    after: &T,
+) {
+    let (before, after) = (format!("{:?}", before), format!("{:?}", after));
+    if before != after {
+        differences.push(Difference {
+            path,
+            before,
+            after,
+        });
+    }
+}
+
+/// Floats are compared bit for bit, so a NaN that stays a NaN isn't a difference but a 0 turning into -0 is
+fn diff_f64(differences: &mut Vec<Difference>, path: String, before: f64, after: f64) {
+    if before.to_bits() != after.to_bits() {
+        differences.push(Difference {
+            path,
+            before: format!("{:?}", before),
+            after: format!("{:?}", after),
+        });
+    }
+}
+
+fn diff_mixture(
+    differences: &mut Vec<Difference>,
+    path: &str,
+    before: &GasMixture,
+    after: &GasMixture,
+) {
+    for (gas, amount) in before.gases.0.iter() {
+        diff_f64(
+            differences,
+            format!("{}.{}", path, gas.id()),
+            *amount,
+            after[gas],
+        );
+    }
+    diff_f64(
+        differences,
+        format!("{}.temperature", path),
+        before.temperature,
+        after.temperature,
+    );
+    diff_f64(
+        differences,
+        format!("{}.volume", path),
+        before.volume,
+        after.volume,
+    );
+}
+
+fn diff_turf(differences: &mut Vec<Difference>, path: &str, before: &Turf, after: &Turf) {
+    match (before, after) {
+        (Turf::Open(before), Turf::Open(after))
+        | (Turf::Immutable(before), Turf::Immutable(after)) => {
+            diff_mixture(differences, path, before, after)
+        }
+        (
+            Turf::Planetary { air, atmosphere },
+            Turf::Planetary {
+                air: after_air,
+                atmosphere: after_atmosphere,
+            },
+        ) => {
+            diff_mixture(differences, path, air, after_air);
+            diff_mixture(
+                differences,
+                &format!("{}.atmosphere", path),
+                atmosphere,
+                after_atmosphere,
+            );
+        }
+        _ => diff_value(differences, path.to_owned(), before, after),
+    }
+}
+
+impl Snapshot {
+    /// Encode the snapshot in the current version of the format.
+    /// Gases are stored by their ids, so snapshots survive gases being added or reordered.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer(MAGIC.to_vec());
+        let gases: Vec<Gas> = GasEnumMap::default().iter().map(|(gas, _)| gas).collect();
+
+        writer.u32(SNAPSHOT_VERSION);
+        writer.usize(gases.len());
+        gases.iter().for_each(|gas| writer.str(gas.id()));
+
+        writer.bool(self.grid.is_some());
+        if let Some(grid) = &self.grid {
+            writer.grid(grid);
+        }
+        writer.usize(self.pipenets.len());
+        self.pipenets
+            .iter()
+            .for_each(|pipenet| writer.mixture(pipenet));
+        writer.usize(self.machines.len());
+        self.machines
+            .iter()
+            .for_each(|placed| writer.placed_machine(placed));
+        writer.usize(self.simulations.len());
+        self.simulations
+            .iter()
+            .for_each(|simulation| writer.simulation(simulation));
+
+        writer.0
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(SnapshotError::NotASnapshot);
+        }
+
+        let mut reader = Reader {
+            bytes,
+            pos: MAGIC.len(),
+            version: 0,
+            gases: Vec::new(),
+        };
+        reader.version = reader.u32()?;
+        if !(OLDEST_SNAPSHOT_VERSION..=SNAPSHOT_VERSION).contains(&reader.version) {
+            return Err(SnapshotError::UnsupportedVersion(reader.version));
+        }
+        for _ in 0..reader.len(8)? {
+            let id = reader.str()?;
+            let gas = Gas::from_id(id).ok_or_else(|| SnapshotError::UnknownGas(id.to_owned()))?;
+            reader.gases.push(gas);
+        }
+
+        let grid = if reader.bool()? {
+            Some(reader.grid()?)
+        } else {
+            None
+        };
+        let pipenets = (0..reader.len(8)?)
+            .map(|_| reader.mixture())
+            .collect::<Result<_, _>>()?;
+        let machines = (0..reader.len(1)?)
+            .map(|_| reader.placed_machine())
+            .collect::<Result<_, _>>()?;
+        let simulations = (0..reader.len(8)?)
+            .map(|_| reader.simulation())
+            .collect::<Result<_, _>>()?;
+
+        if reader.pos != bytes.len() {
+            return Err(SnapshotError::Invalid(reader.pos));
+        }
+
+        Ok(Self {
+            grid,
+            pipenets,
+            machines,
+            simulations,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Everything that changed between `self` and `other`, in the order it appears in the snapshot
+    pub fn diff(&self, other: &Snapshot) -> Vec<Difference> {
+        let mut differences = Vec::new();
+
+        match (&self.grid, &other.grid) {
+            (Some(before), Some(after))
+                if (before.width, before.height) == (after.width, after.height) =>
+            {
+                for (i, (turf, other_turf)) in before.turfs.iter().zip(&after.turfs).enumerate() {
+                    diff_turf(
+                        &mut differences,
+                        &format!("grid.turfs[{}]", i),
+                        turf,
+                        other_turf,
+                    );
+                }
+                for i in 0..before.turfs.len() {
+                    diff_value(
+                        &mut differences,
+                        format!("grid.active[{}]", i),
+                        &before.active.contains(&i),
+                        &after.active.contains(&i),
+                    );
+                }
+                diff_value(
+                    &mut differences,
+                    "grid.excited_groups".to_owned(),
+                    &before.excited_groups,
+                    &after.excited_groups,
+                );
+                diff_value(
+                    &mut differences,
+                    "grid.space_is_all_consuming".to_owned(),
+                    &before.space_is_all_consuming,
+                    &after.space_is_all_consuming,
+                );
+            }
+            (Some(before), Some(after)) => diff_value(
+                &mut differences,
+                "grid.size".to_owned(),
+                &(before.width, before.height),
+                &(after.width, after.height),
+            ),
+            (before, after) => diff_value(
+                &mut differences,
+                "grid".to_owned(),
+                &before.is_some(),
+                &after.is_some(),
+            ),
+        }
+
+        diff_value(
+            &mut differences,
+            "pipenets.len".to_owned(),
+            &self.pipenets.len(),
+            &other.pipenets.len(),
+        );
+        for (i, (pipenet, other_pipenet)) in self.pipenets.iter().zip(&other.pipenets).enumerate() {
+            diff_mixture(
+                &mut differences,
+                &format!("pipenets[{}]", i),
+                pipenet,
+                other_pipenet,
+            );
+        }
+
+        diff_value(
+            &mut differences,
+            "machines.len".to_owned(),
+            &self.machines.len(),
+            &other.machines.len(),
+        );
+        for (i, (placed, other_placed)) in self.machines.iter().zip(&other.machines).enumerate() {
+            diff_value(
+                &mut differences,
+                format!("machines[{}]", i),
+                placed,
+                other_placed,
+            );
+        }
+
+        diff_value(
+            &mut differences,
+            "simulations.len".to_owned(),
+            &self.simulations.len(),
+            &other.simulations.len(),
+        );
+        for (i, (simulation, other_simulation)) in
+            self.simulations.iter().zip(&other.simulations).enumerate()
+        {
+            diff_mixture(
+                &mut differences,
+                &format!("simulations[{}]", i),
+                &simulation.mixture,
+                &other_simulation.mixture,
+            );
+            diff_value(
+                &mut differences,
+                format!("simulations[{}].steps", i),
+                &simulation.steps,
+                &other_simulation.steps,
+            );
+            diff_value(
+                &mut differences,
+                format!("simulations[{}].output", i),
+                &simulation.output,
+                &other_simulation.output,
+            );
+            diff_value(
+                &mut differences,
+                format!("simulations[{}].rng", i),
+                &simulation.rng,
+                &other_simulation.rng,
+            );
+        }
+
+        differences
+    }
+}
@@ -0,0 +1,201 @@
+use crate::constants as C;
+use crate::{gen_gas_vec, Gas, GasMixture, GasVec};
+
+/// What happened to a supermatter crystal over a tick
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SupermatterOutput {
+    /// Power of the crystal after the tick
+    pub power: f64,
+    /// Damage of the crystal after the tick
+    pub damage: f64,
+    /// Strength of the radiation pulse, the crystal only pulses on half of the ticks in game
+    pub radiation: f64,
+    /// Gases the crystal released into the environment
+    pub emitted: GasVec,
+}
+
+/// A supermatter crystal reacting with the mixture around it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Supermatter {
+    pub power: f64,
+    pub damage: f64,
+    /// Power waiting to be absorbed from things that hit the crystal
+    pub matter_power: f64,
+    /// How much CO2 currently stops the power from decaying, from 0 to 1
+    pub powerloss_dynamic_scaling: f64,
+    pub(crate) powerloss_inhibitor: f64,
+    // Damage is dealt based on the gas of the previous tick
+    pub(crate) mole_heat_penalty: f64,
+    pub(crate) dynamic_heat_resistance: f64,
+    pub(crate) combined_gas: f64,
+}
+
+impl Default for Supermatter {
+    fn default() -> Self {
+        Self {
+            power: 0.,
+            damage: 0.,
+            matter_power: 0.,
+            powerloss_dynamic_scaling: 0.,
+            powerloss_inhibitor: 1.,
+            mole_heat_penalty: 0.,
+            dynamic_heat_resistance: 1.,
+            combined_gas: 0.,
+        }
+    }
+}
+
+impl Supermatter {
+    /// Whether the crystal took enough damage to delaminate
+    pub fn is_delaminating(&self) -> bool {
+        self.damage > C::SUPERMATTER_EXPLOSION_POINT
+    }
+
+    /// Process the crystal for a tick, returning `environment` after the crystal took in and let out its share of the gas
+    pub fn process(&mut self, environment: &GasMixture) -> (GasMixture, SupermatterOutput) {
+        let damage_archived = self.damage;
+        let (rest, removed) = environment.remove_ratio(C::SUPERMATTER_GAS_EFFICIENCY);
+        let total_moles = removed.get_total_amount();
+
+        if total_moles <= 0. || removed.get_heat_cap() <= 0. {
+            self.damage += (self.power / 1000. * C::DAMAGE_INCREASE_MULTIPLIER).max(0.1);
+            self.decay_power();
+
+            return (
+                *environment,
+                SupermatterOutput {
+                    power: self.power,
+                    damage: self.damage,
+                    radiation: 0.,
+                    emitted: gen_gas_vec!(),
+                },
+            );
+        }
+
+        self.damage = (self.damage
+            + ((total_moles / 200.).clamp(0.5, 1.) * removed.temperature
+                - (C::T0C + C::HEAT_PENALTY_THRESHOLD) * self.dynamic_heat_resistance)
+                .max(0.)
+                * self.mole_heat_penalty
+                / 150.
+                * C::DAMAGE_INCREASE_MULTIPLIER)
+            .max(0.);
+        self.damage = (self.damage
+            + (self.power - C::POWER_PENALTY_THRESHOLD).max(0.) / 500.
+                * C::DAMAGE_INCREASE_MULTIPLIER)
+            .max(0.);
+        self.damage = (self.damage
+            + (self.combined_gas - C::MOLE_PENALTY_THRESHOLD).max(0.) / 80.
+                * C::DAMAGE_INCREASE_MULTIPLIER)
+            .max(0.);
+        if self.combined_gas < C::MOLE_PENALTY_THRESHOLD {
+            self.damage = (self.damage
+                + (removed.temperature - (C::T0C + C::HEAT_PENALTY_THRESHOLD)).min(0.) / 150.)
+                .max(0.);
+        }
+        self.damage = self
+            .damage
+            .min(damage_archived + C::DAMAGE_HARDCAP * C::SUPERMATTER_EXPLOSION_POINT);
+
+        self.combined_gas = total_moles;
+        let comp = |gas: Gas| (removed[gas] / total_moles).max(0.);
+        let plasma = comp(Gas::Pl);
+        let oxygen = comp(Gas::O2);
+        let co2 = comp(Gas::CO2);
+        let pluoxium = comp(Gas::PlOx);
+        let tritium = comp(Gas::H2);
+        let bz = comp(Gas::BZ);
+        let n2o = comp(Gas::N2O);
+        let nitrogen = comp(Gas::N2);
+        let pluoxium_bonus = if pluoxium >= 0.15 { 1. } else { 0. };
+
+        let gasmix_power_ratio =
+            (plasma + oxygen + co2 + tritium + bz - pluoxium - nitrogen).clamp(0., 1.);
+        let dynamic_heat_modifier = (plasma * C::PLASMA_HEAT_PENALTY
+            + oxygen * C::OXYGEN_HEAT_PENALTY
+            + co2 * C::CO2_HEAT_PENALTY
+            + tritium * C::TRITIUM_HEAT_PENALTY
+            + pluoxium * C::PLUOXIUM_HEAT_PENALTY * pluoxium_bonus
+            + nitrogen * C::NITROGEN_HEAT_PENALTY
+            + bz * C::BZ_HEAT_PENALTY)
+            .max(0.5);
+        self.dynamic_heat_resistance = (n2o * C::N2O_HEAT_RESISTANCE
+            + pluoxium * C::PLUOXIUM_HEAT_RESISTANCE * pluoxium_bonus)
+            .max(1.);
+        let power_transmission_bonus = plasma * C::PLASMA_TRANSMIT_MODIFIER
+            + oxygen * C::OXYGEN_TRANSMIT_MODIFIER
+            + bz * C::BZ_TRANSMIT_MODIFIER
+            + tritium * C::TRITIUM_TRANSMIT_MODIFIER
+            + pluoxium * C::PLUOXIUM_TRANSMIT_MODIFIER * pluoxium_bonus;
+        self.mole_heat_penalty = (total_moles / C::MOLE_HEAT_PENALTY).max(0.25);
+
+        if total_moles > C::POWERLOSS_INHIBITION_MOLE_THRESHOLD
+            && co2 > C::POWERLOSS_INHIBITION_GAS_THRESHOLD
+        {
+            self.powerloss_dynamic_scaling = (self.powerloss_dynamic_scaling
+                + (co2 - self.powerloss_dynamic_scaling).clamp(-0.02, 0.02))
+            .clamp(0., 1.);
+        } else {
+            self.powerloss_dynamic_scaling = (self.powerloss_dynamic_scaling - 0.05).clamp(0., 1.);
+        }
+        self.powerloss_inhibitor = (1.
+            - self.powerloss_dynamic_scaling
+                * (total_moles / C::POWERLOSS_INHIBITION_MOLE_BOOST_THRESHOLD).clamp(1., 1.5))
+        .clamp(0., 1.);
+
+        if self.matter_power > 0. {
+            let removed_matter = (self.matter_power / C::MATTER_POWER_CONVERSION).max(40.);
+            self.power = (self.power + removed_matter).max(0.);
+            self.matter_power = (self.matter_power - removed_matter).max(0.);
+        }
+
+        let temp_factor = if gasmix_power_ratio > 0.8 { 50. } else { 30. };
+        self.power =
+            (removed.temperature * temp_factor / C::T0C * gasmix_power_ratio + self.power).max(0.);
+
+        // The bracketing mirrors the game, where the transmission bonus only scales the pluoxium term
+        let radiation = self.power
+            * (1.
+                + tritium * C::TRITIUM_RADIOACTIVITY_MODIFIER
+                + pluoxium
+                    * C::PLUOXIUM_RADIOACTIVITY_MODIFIER
+                    * pluoxium_bonus
+                    * (power_transmission_bonus / (10. - bz * C::BZ_RADIOACTIVITY_MODIFIER)));
+
+        let device_energy = self.power * C::REACTION_POWER_MODIFIER;
+        let temperature = (removed.temperature
+            + device_energy * dynamic_heat_modifier / C::THERMAL_RELEASE_MODIFIER)
+            .min(2500. * dynamic_heat_modifier)
+            .max(0.);
+        let emitted = gen_gas_vec!(
+            Gas::Pl => (device_energy * dynamic_heat_modifier / C::PLASMA_RELEASE_MODIFIER).max(0.),
+            Gas::O2 => ((device_energy + temperature * dynamic_heat_modifier - C::T0C)
+                / C::OXYGEN_RELEASE_MODIFIER)
+                .max(0.),
+        );
+        let removed = GasMixture {
+            gases: removed.gases + emitted,
+            temperature,
+            ..removed
+        };
+
+        self.decay_power();
+
+        (
+            rest.assume(&removed),
+            SupermatterOutput {
+                power: self.power,
+                damage: self.damage,
+                radiation,
+                emitted,
+            },
+        )
+    }
+
+    /// Lose a cubic share of the power, never more than 83% of it at once
+    fn decay_power(&mut self) {
+        let loss = ((self.power / 500.).powi(3) * self.powerloss_inhibitor)
+            .min(self.power * 0.83 * self.powerloss_inhibitor);
+        self.power = (self.power - loss).max(0.);
+    }
+}
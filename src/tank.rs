@@ -0,0 +1,131 @@
+use crate::constants as C;
+use crate::reactions as R;
+use crate::timeline::Timeline;
+use crate::GasMixture;
+
+/// Ranges of the explosion of a fragmenting tank, in tiles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExplosionRange {
+    /// Range before it's split into the ranges of the explosion, it's what research points are based on
+    pub range: f64,
+    pub devastation: i32,
+    pub heavy: i32,
+    pub light: i32,
+    pub flash: i32,
+}
+
+impl ExplosionRange {
+    pub fn from_pressure(pressure: f64) -> Self {
+        let range = (pressure - C::TANK_FRAGMENT_PRESSURE) / C::TANK_FRAGMENT_SCALE;
+
+        Self {
+            range,
+            devastation: (range * 0.25).floor() as i32,
+            heavy: (range * 0.5).floor() as i32,
+            light: range.floor() as i32,
+            flash: (range * 1.5).floor() as i32,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TankFailure {
+    /// The tank released all of its gas at once
+    Rupture,
+    Explosion(ExplosionRange),
+}
+
+/// A gas tank that leaks, ruptures or explodes when overpressurized
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tank {
+    pub air: GasMixture,
+    /// Ticks the tank can stay overpressurized before it starts leaking or ruptures
+    pub integrity: u32,
+}
+
+impl Tank {
+    pub fn new(air: GasMixture) -> Self {
+        Self {
+            air,
+            integrity: C::TANK_MAX_INTEGRITY,
+        }
+    }
+
+    /// Check the pressure of the tank after it reacted, returning how it failed, if it did.
+    /// A fragmenting tank reacts once more to build up pressure before it explodes, leaking tanks lose some of their gas.
+    pub fn check_status(&mut self) -> Option<TankFailure> {
+        let pressure = self.air.get_pressure();
+
+        if pressure > C::TANK_FRAGMENT_PRESSURE {
+            self.air = R::react_once(self.air);
+            return Some(TankFailure::Explosion(ExplosionRange::from_pressure(
+                self.air.get_pressure(),
+            )));
+        }
+
+        if pressure > C::TANK_RUPTURE_PRESSURE || self.air.temperature > C::TANK_MELT_TEMPERATURE {
+            if self.integrity == 0 {
+                self.air = self.air.remove_ratio(1.).0;
+                return Some(TankFailure::Rupture);
+            }
+            self.integrity -= 1;
+        } else if pressure > C::TANK_LEAK_PRESSURE {
+            if self.integrity == 0 {
+                self.air = self.air.remove_ratio(C::TANK_LEAK_RATIO).0;
+            } else {
+                self.integrity -= 1;
+            }
+        } else if self.integrity < C::TANK_MAX_INTEGRITY {
+            self.integrity += 1;
+        }
+
+        None
+    }
+}
+
+/// How a tank transfer valve bomb went
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferValveResult {
+    /// The merged mixture and its state after every tick, the tank checks its status after reacting
+    pub timeline: Timeline,
+    pub failure: Option<TankFailure>,
+    /// Step of the timeline the tank failed on
+    pub failed_at: Option<usize>,
+}
+
+/// Open the valve between `tank_one` and `tank_two`, merging them into a single tank of their combined volume,
+/// and react it for up to `max_steps` ticks or until it fails
+pub fn transfer_valve(
+    tank_one: &GasMixture,
+    tank_two: &GasMixture,
+    max_steps: usize,
+) -> TransferValveResult {
+    let mut tank = Tank::new(tank_one.mix_with(tank_two));
+    let mut timeline = Timeline::new(tank.air);
+
+    for step in 1..=max_steps {
+        if tank.air.get_heat_cap() <= 0. {
+            break;
+        }
+
+        let (air, output) = R::react_once_with_output(tank.air);
+        tank.air = air;
+
+        let failure = tank.check_status();
+        timeline.push(tank.air, output);
+
+        if failure.is_some() {
+            return TransferValveResult {
+                timeline,
+                failure,
+                failed_at: Some(step),
+            };
+        }
+    }
+
+    TransferValveResult {
+        timeline,
+        failure: None,
+        failed_at: None,
+    }
+}
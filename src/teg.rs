@@ -0,0 +1,81 @@
+use crate::constants as C;
+use crate::{reactions as R, GasMixture};
+
+/// The result of a single tick of a thermoelectric generator
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TegTick {
+    /// Energy turned into electricity this tick
+    pub power: f64,
+    pub hot: GasMixture,
+    pub cold: GasMixture,
+}
+
+/// A thermoelectric generator between a hot and a cold circulator.
+/// The whole mixture of each loop exchanges heat, not just what the circulators pump in a tick.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Teg {
+    /// Share of the transferred heat that becomes power, the rest ends up in the cold loop
+    pub efficiency: f64,
+}
+
+impl Default for Teg {
+    fn default() -> Self {
+        Self {
+            efficiency: C::TEG_EFFICIENCY,
+        }
+    }
+}
+
+impl Teg {
+    /// Move heat from `hot` to `cold` for a tick.
+    /// Nothing happens unless the hot loop is actually hotter.
+    pub fn process(&self, hot: &GasMixture, cold: &GasMixture) -> TegTick {
+        let hot_heat_capacity = hot.get_heat_cap();
+        let cold_heat_capacity = cold.get_heat_cap();
+        let delta_temperature = hot.temperature - cold.temperature;
+
+        if delta_temperature <= 0. || hot_heat_capacity <= 0. || cold_heat_capacity <= 0. {
+            return TegTick {
+                power: 0.,
+                hot: *hot,
+                cold: *cold,
+            };
+        }
+
+        let energy_transfer = delta_temperature * hot_heat_capacity * cold_heat_capacity
+            / (hot_heat_capacity + cold_heat_capacity);
+        let heat = energy_transfer * (1. - self.efficiency);
+
+        TegTick {
+            power: energy_transfer * self.efficiency,
+            hot: GasMixture {
+                temperature: hot.temperature - energy_transfer / hot_heat_capacity,
+                ..*hot
+            },
+            cold: GasMixture {
+                temperature: cold.temperature + heat / cold_heat_capacity,
+                ..*cold
+            },
+        }
+    }
+
+    /// Run the generator for `ticks` ticks, reacting the hot loop once before every tick
+    pub fn run(&self, hot: &GasMixture, cold: &GasMixture, ticks: usize) -> Vec<TegTick> {
+        let mut hot = *hot;
+        let mut cold = *cold;
+
+        (1..=ticks)
+            .map(|_| {
+                if hot.get_heat_cap() > 0. {
+                    hot = R::react_once(hot);
+                }
+
+                let tick = self.process(&hot, &cold);
+                hot = tick.hot;
+                cold = tick.cold;
+
+                tick
+            })
+            .collect()
+    }
+}
@@ -1,7 +1,29 @@
 #[cfg(test)]
 mod tests {
+    use crate::canister::Canister;
+    use crate::constants as C;
+    use crate::diagnostics as D;
+    use crate::dmm::{self as M, DmmError, DmmRule, TileKind};
+    use crate::equalization::Transfer;
+    use crate::ffi as F;
+    use crate::filters::{Filter, Scrubber, ScrubberMode};
+    use crate::gas_mixture::AtmosStringError;
+    use crate::golden::{self as G, Trace, TraceError};
+    use crate::grid::{Grid, TickStats, Turf};
+    use crate::hotspot::{FireArea, Hotspot};
     use crate::reactions as R;
-    use crate::{gen_gas_mix_with_temp, temperature, test_reaction, Gas, GasMixture};
+    use crate::rng::{self, Rng};
+    use crate::simulation::{Simulation, Stop, StopCondition};
+    use crate::snapshot::{Machine, PlacedMachine, Snapshot, SnapshotError, SNAPSHOT_VERSION};
+    use crate::supermatter::Supermatter;
+    use crate::tank::{self as T, ExplosionRange, Tank, TankFailure};
+    use crate::teg::Teg;
+    use crate::thermomachine::{HeatExchanger, Thermomachine, ThermomachineMode};
+    use crate::timeline::Timeline;
+    use crate::{
+        enum_map, gen_gas_mix_with_temp, reaction, temperature, test_reaction, test_traces, Gas,
+        GasMixture,
+    };
     use float_cmp::approx_eq;
 
     #[test]
@@ -78,253 +100,2133 @@ mod tests {
         );
     }
 
-    test_reaction!(
-        named(n2o_decomp_test)
-        testing(R::n2o_decomp)
-        init_with(
-            Gas::N2O => 20.0
+    test_traces!(
+        from(include_str!("../traces/reference.trace"))
+        named(
+            n2o_decomp_test,
+            trit_fire_test_as_oxidizer,
+            trit_fire_test_as_fission,
+            plasma_fire_test_as_oxidizer,
+            plasma_fire_test_as_fusion,
+            nitryl_formation_test,
+            bz_react_test_nitrous_reduction,
+            bz_react_test_bz_formation,
+            stimulum_test,
+            nob_synth_test,
+            fusion_test_suppressed,
+            fusion_test_endo,
+            fusion_test_exo,
+            random_react_test,
         )
-        init_at(temperature!(10000.0, K))
-        expect_with(
-            Gas::N2 => 3.6,
-            Gas::O2 => 1.8,
-            Gas::N2O => 16.4
-        )
-        expect_at(temperature!(11413.612565445026, K))
     );
 
-    test_reaction!(
-        named(trit_fire_test_as_oxidizer)
-        testing(R::trit_fire)
-        init_with(
-            Gas::H2 => 100.0,
-            Gas::O2 => 50.0
-        )
-        init_at(temperature!(500., K))
-        expect_with(
-            Gas::O2 => 50.0,
-            Gas::H2O => 0.5,
-            Gas::H2 => 99.5
+    #[test]
+    fn research_output_test() {
+        let mut output = R::ReactionOutput::default();
+        R::miasma_sterilization(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::Miasma => 100.0,
+                    Gas::N2 => 100.0,
+                )
+                at(temperature!(1000.0, K))
+            ),
+            &mut output,
+            None,
+        );
+        R::hnob_synth(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2 => 25.0,
+                    Gas::H2 => 15.0,
+                )
+                at(temperature!(5000000.0, K))
+            ),
+            &mut output,
+            None,
+        );
+
+        assert!(
+            approx_eq!(f64, output.research, 47.8425 * 40. + 0.4 * 1000.),
+            "Wrong amount of research: {}",
+            output.research
+        );
+    }
+
+    #[test]
+    fn fusion_output_test_exo() {
+        let mut output = R::ReactionOutput::default();
+        R::fusion(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::CO2 => 2500.0,
+                    Gas::Pl => 500.0,
+                    Gas::H2 => 1.5,
+                )
+                at(temperature!(500000.0, K))
+            ),
+            &mut output,
+            None,
+        );
+
+        assert!(output.fusion.happened, "Fusion didn't happen");
+        assert_eq!(output.fusion.thermality, R::Thermality::Exothermic);
+        assert!(
+            output.fusion.particle_chance > 0. && output.fusion.particle_chance < 1.,
+            "Wrong particle chance: {}",
+            output.fusion.particle_chance
+        );
+    }
+
+    #[test]
+    fn fusion_output_test_endo() {
+        let mut output = R::ReactionOutput::default();
+        R::fusion(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::CO2 => 2500.0,
+                    Gas::Pl => 500.0,
+                    Gas::BZ => 50.0,
+                    Gas::H2 => 1.5,
+                )
+                at(temperature!(500000.0, K))
+                in(1000.0)
+            ),
+            &mut output,
+            None,
+        );
+
+        assert!(output.fusion.happened, "Fusion didn't happen");
+        assert_eq!(output.fusion.thermality, R::Thermality::Endothermic);
+        assert!(
+            output.fusion.reaction_energy < 0.,
+            "Wrong reaction energy: {}",
+            output.fusion.reaction_energy
+        );
+    }
+
+    #[test]
+    fn fusion_output_test_no_fusion() {
+        let mut output = R::ReactionOutput::default();
+        R::fusion(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::CO2 => 2500.0,
+                    Gas::Pl => 500.0,
+                    Gas::H2 => 1.5,
+                )
+                at(temperature!(5000.0, K))
+            ),
+            &mut output,
+            None,
+        );
+
+        assert_eq!(output.fusion, R::FusionOutput::default());
+    }
+
+    #[test]
+    fn trit_fire_radiation_test() {
+        let mut output = R::ReactionOutput::default();
+        R::trit_fire(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::H2 => 100.0,
+                    Gas::O2 => 500.0,
+                )
+                at(temperature!(500., K))
+            ),
+            &mut output,
+            None,
+        );
+
+        assert!(
+            approx_eq!(f64, output.radiation, 5600.0),
+            "Wrong radiation: {}",
+            output.radiation
+        );
+    }
+
+    #[test]
+    fn hotspot_ignition_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let inert = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        let (hotspot, burned) = Hotspot::ignite(air, 1000.0, 5.0);
+        assert!(
+            hotspot.is_some(),
+            "Hot enough exposure didn't ignite the air"
+        );
+        assert!(
+            burned[Gas::Pl] < air[Gas::Pl],
+            "Ignition didn't burn any plasma"
+        );
+
+        assert_eq!(Hotspot::ignite(air, 300.0, 5.0), (None, air));
+        assert_eq!(Hotspot::ignite(inert, 1000.0, 5.0), (None, inert));
+    }
+
+    #[test]
+    fn hotspot_burnout_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let hotspot = Hotspot {
+            just_spawned: false,
+            ..Hotspot::new(125.0, 1000.0)
+        };
+
+        assert_eq!(hotspot.process(air, 0.0), (None, air));
+    }
+
+    #[test]
+    fn hotspot_exposure_volume_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 50.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let hotspot = Hotspot {
+            just_spawned: false,
+            ..Hotspot::new(125.0, 1000.0)
+        };
+
+        let (_, exposed) = hotspot.perform_exposure(GasMixture { volume: 0.0, ..air }, 0.0);
+        assert_eq!(exposed, GasMixture { volume: 0.0, ..air });
+
+        let (_, exposed) = hotspot.perform_exposure(
+            GasMixture {
+                volume: 50.0,
+                ..air
+            },
+            0.0,
+        );
+        assert!(exposed.get_total_amount() > 0.0);
+        assert!(exposed.temperature.is_finite());
+        assert!(exposed.gases.0.values().all(|amount| *amount >= 0.0));
+    }
+
+    #[test]
+    fn fire_spread_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 50.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let mut area = FireArea::new(vec![air; 3], vec![vec![1], vec![0, 2], vec![1]]);
+        area.expose(0, 1000.0, 5.0);
+
+        for _ in 0..20 {
+            area.tick();
+        }
+
+        assert!(area.hotspots[2].is_some(), "Fire didn't spread");
+        assert!(
+            area.airs[2].temperature > air.temperature,
+            "Fire didn't heat up the air"
+        );
+    }
+
+    reaction!(
+        called(immune_decomp)
+        described_as(IMMUNE_DECOMP, "Test Decomposition")
+        with(
+            Gas::N2O => 1.0
         )
-        expect_at(temperature!(565.7568238213399, K))
+        producing(Gas::N2)
+        at(0.0)
+        suppressed_by(R::Suppression::Immune)
+        with_gm_as(gm) => {
+            gm + gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2O => -1.0,
+                    Gas::N2 => 1.0,
+                )
+                at(gm.temperature)
+            )
+        }
     );
 
-    test_reaction!(
-        named(trit_fire_test_as_fission)
-        testing(R::trit_fire)
-        init_with(
-            Gas::H2 => 100.0,
-            Gas::O2 => 500.0
-        )
-        init_at(temperature!(500., K))
-        expect_with(
-            Gas::O2 => 410.0,
-            Gas::H2O => 100.0,
-            Gas::H2 => 90.0
+    reaction!(
+        called(partial_decomp)
+        described_as(PARTIAL_DECOMP, "Test Decomposition")
+        with(
+            Gas::N2O => 1.0
         )
-        expect_at(temperature!(21793.893129770993, K))
+        producing(Gas::N2)
+        at(0.0)
+        suppressed_by(R::Suppression::Partial { threshold: 1.0, factor: 0.25 })
+        with_gm_as(gm)
+        reporting_to(output) => {
+            output.research += 4.0;
+            output.fire += 1.0;
+            gm + gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2O => -1.0,
+                    Gas::N2 => 1.0,
+                )
+                at(gm.temperature)
+            )
+        }
     );
 
     test_reaction!(
-        named(plasma_fire_test_as_oxidizer)
-        testing(R::plasma_fire)
+        named(hnob_suppression_test)
+        testing(R::n2o_decomp)
         init_with(
-            Gas::Pl => 100.0,
-            Gas::O2 => 100.0
+            Gas::N2O => 20.0,
+            Gas::HNb => 5.0
         )
-        init_at(temperature!(500., K))
+        init_at(temperature!(10000.0, K))
         expect_with(
-            Gas::O2 => 99.85571305137054,
-            Gas::CO2 => 0.1109798775153106,
-            Gas::Pl => 99.8890201224847
+            Gas::N2O => 20.0,
+            Gas::HNb => 5.0
         )
-        expect_at(temperature!(515.6434578678881, K))
+        expect_at(temperature!(10000.0, K))
     );
 
     test_reaction!(
-        named(plasma_fire_test_as_fusion)
-        testing(R::plasma_fire)
+        named(hnob_suppression_test_immune)
+        testing(immune_decomp)
         init_with(
-            Gas::Pl => 100.0,
-            Gas::O2 => 10000.0
+            Gas::N2O => 20.0,
+            Gas::HNb => 50.0
         )
-        init_at(temperature!(500., K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::O2 => 9998.557130513705,
-            Gas::Pl => 98.89020122484689,
-            Gas::H2 => 1.109798775153106
+            Gas::N2O => 19.0,
+            Gas::N2 => 1.0,
+            Gas::HNb => 50.0
         )
-        expect_at(temperature!(515.6955382962315, K))
+        expect_at(temperature!(1000.0, K))
     );
 
     test_reaction!(
-        named(nitryl_formation_test)
-        testing(R::nitryl_formation)
+        named(hnob_suppression_test_partial)
+        testing(partial_decomp)
         init_with(
-            Gas::N2 => 100.0,
-            Gas::O2 => 100.0,
-            Gas::PlOx => 5.0
+            Gas::N2O => 20.0,
+            Gas::HNb => 5.0
         )
-        init_at(temperature!(50000.0, K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::N2 => 97.76676046272723,
-            Gas::O2 => 97.76676046272723,
-            Gas::NO2 => 4.466479074545536,
-            Gas::PlOx => 5.0
+            Gas::N2O => 19.75,
+            Gas::N2 => 0.25,
+            Gas::HNb => 5.0
         )
-        expect_at(temperature!(49949.244555971076, K))
+        expect_at(temperature!(1000.0, K))
     );
 
-    test_reaction!(
-        named(bz_react_test_nitrous_reduction)
-        testing(R::bz_synth)
-        init_with(
-            Gas::N2O => 15.0,
-            Gas::Pl => 15.0
+    #[test]
+    fn hnob_suppression_partial_output_test() {
+        let react = |noblium: f64| {
+            let mut output = R::ReactionOutput::default();
+            partial_decomp(
+                gen_gas_mix_with_temp!(
+                    with(
+                        Gas::N2O => 20.0,
+                        Gas::HNb => noblium,
+                    )
+                    at(temperature!(1000.0, K))
+                ),
+                &mut output,
+                None,
+            );
+            output
+        };
+
+        let free = react(0.0);
+        assert_eq!(free.research, 4.0);
+        assert_eq!(free.fire, 1.0);
+
+        let suppressed = react(5.0);
+        assert!(suppressed.suppressed);
+        assert_eq!(suppressed.research, 1.0);
+        assert_eq!(suppressed.fire, 0.25);
+    }
+
+    #[test]
+    fn hnob_suppression_output_test() {
+        let suppressed = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2O => 20.0,
+                Gas::HNb => 5.0,
+            )
+            at(temperature!(10000.0, K))
+        );
+        let (_, output) = R::react_once_with_output(suppressed);
+        assert!(output.suppressed, "Suppression is not reported");
+
+        let inert = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 20.0,
+                Gas::HNb => 5.0,
+            )
+            at(temperature!(10000.0, K))
+        );
+        let (_, output) = R::react_once_with_output(inert);
+        assert!(
+            !output.suppressed,
+            "Suppression is reported without any reaction to suppress"
+        );
+    }
+
+    reaction!(
+        called(pressurized_decomp)
+        described_as(PRESSURIZED_DECOMP, "Test Decomposition")
+        with(
+            Gas::N2O => 1.0
         )
-        init_at(temperature!(2.0, K))
-        expect_with(
-            Gas::N2O => 7.5
+        producing(Gas::N2)
+        at(0.0)
+        when(
+            MinPressure(100.0),
+            MaxPressure(1000.0),
+            MinEnergy(500000.0)
         )
-        expect_at(temperature!(5024.0, K))
-        in(2500.0)
+        with_gm_as(gm) => {
+            gm + gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2O => -1.0,
+                    Gas::N2 => 1.0,
+                )
+                at(gm.temperature)
+            )
+        }
     );
 
     test_reaction!(
-        named(bz_react_test_bz_formation)
-        testing(R::bz_synth)
+        named(pressure_window_test)
+        testing(pressurized_decomp)
         init_with(
-            Gas::N2O => 15.0,
-            Gas::Pl => 45.0
+            Gas::N2O => 20.0
         )
-        init_at(temperature!(2.0, K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::O2 => 1.0,
-            Gas::Pl => 15.0,
-            Gas::BZ => 14.0
+            Gas::N2O => 19.0,
+            Gas::N2 => 1.0
         )
-        expect_at(temperature!(999.7350993377484, K))
-        in(2500.0)
+        expect_at(temperature!(1000.0, K))
     );
 
     test_reaction!(
-        named(stimulum_test)
-        testing(R::stimulum_synth)
+        named(pressure_window_test_overpressure)
+        testing(pressurized_decomp)
         init_with(
-            Gas::H2 => 50.0,
-            Gas::Pl => 20.0,
-            Gas::BZ => 50.0,
-            Gas::NO2 => 50.0
+            Gas::N2O => 20.0
         )
-        init_at(temperature!(100000., K))
+        init_at(temperature!(10000.0, K))
         expect_with(
-            Gas::Pl => 19.0,
-            Gas::NO2 => 49.0,
-            Gas::H2 => 49.0,
-            Gas::BZ => 50.0,
-            Gas::ST => 0.1
+            Gas::N2O => 20.0
         )
-        expect_at(temperature!(104354.42587722163, K))
+        expect_at(temperature!(10000.0, K))
     );
 
     test_reaction!(
-        named(nob_synth_test)
-        testing(R::hnob_synth)
+        named(min_energy_test)
+        testing(pressurized_decomp)
         init_with(
-            Gas::N2 => 25.0,
-            Gas::H2 => 15.0
+            Gas::N2O => 20.0
         )
-        init_at(temperature!(5000000.0, K))
+        init_at(temperature!(500.0, K))
         expect_with(
-            Gas::N2 => 17.0,
-            Gas::HNb => 0.4,
-            Gas::H2 => 11.0
+            Gas::N2O => 20.0
         )
-        expect_at(temperature!(1960000.0, K))
+        expect_at(temperature!(500.0, K))
+        in(500.0)
     );
 
-    test_reaction!(
-        named(fusion_test_suppressed)
-        testing(R::fusion)
-        init_with(
-            Gas::CO2 => 2500.0,
-            Gas::Pl => 500.0,
-            Gas::BZ => 50.0,
-            Gas::H2 => 1.5
+    reaction!(
+        called(ratio_decomp)
+        described_as(RATIO_DECOMP, "Test Decomposition")
+        with(
+            Gas::N2O => 1.0
         )
-        init_at(temperature!(500000.0, K))
-        expect_with(
-            Gas::CO2 => 576.5539197230712,
-            Gas::Pl => 2173.446080276929,
-            Gas::H2 => 0.5,
-            Gas::BZ => 50.0
+        producing(Gas::N2)
+        at(0.0)
+        when(
+            MinRatio(Gas::N2O, 0.5)
         )
-        expect_at(temperature!(500000.0, K))
+        with_gm_as(gm) => {
+            gm + gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2O => -1.0,
+                    Gas::N2 => 1.0,
+                )
+                at(gm.temperature)
+            )
+        }
     );
 
     test_reaction!(
-        named(fusion_test_endo)
-        testing(R::fusion)
+        named(min_ratio_test)
+        testing(ratio_decomp)
         init_with(
-            Gas::CO2 => 2500.0,
-            Gas::Pl => 500.0,
-            Gas::BZ => 100.0,
-            Gas::H2 => 1.5
+            Gas::N2O => 10.0,
+            Gas::N2 => 10.0
         )
-        init_at(temperature!(500000.0, K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::CO2 => 1551.3402280453292,
-            Gas::Pl => 1198.6597719546708,
-            Gas::NO2 => 2.7172818684608555,
-            Gas::H2 => 0.5,
-            Gas::BZ => 102.71728186846086
+            Gas::N2O => 9.0,
+            Gas::N2 => 11.0
         )
-        expect_at(temperature!(210716.17990979773, K))
+        expect_at(temperature!(1000.0, K))
     );
 
     test_reaction!(
-        named(fusion_test_exo)
-        testing(R::fusion)
+        named(min_ratio_test_below)
+        testing(ratio_decomp)
         init_with(
-            Gas::CO2 => 2500.0,
-            Gas::Pl => 500.0,
-            Gas::H2 => 1.5
+            Gas::N2O => 10.0,
+            Gas::N2 => 10.1
         )
-        init_at(temperature!(500000.0, K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::O2 => 9.115832060388129e-06,
-            Gas::CO2 => 250.00455791603008,
-            Gas::N2O => 9.115832060388129e-06,
-            Gas::Pl => 499.9954420839698,
-            Gas::H2 => 0.5
+            Gas::N2O => 10.0,
+            Gas::N2 => 10.1
         )
-        expect_at(temperature!(813992.1067058449, K))
+        expect_at(temperature!(1000.0, K))
     );
 
     test_reaction!(
-        named(random_react_test)
-        testing(R::react_once)
+        named(min_ratio_test_above)
+        testing(ratio_decomp)
         init_with(
-            Gas::N2 => 522.0,
-            Gas::O2 => 970.0,
-            Gas::CO2 => 358.0,
-            Gas::N2O => 298.0,
-            Gas::Pl => 582.0,
-            Gas::H2 => 134.0,
-            Gas::BZ => 74.0
+            Gas::N2O => 10.1,
+            Gas::N2 => 10.0
         )
-        init_at(temperature!(6000194.0, K))
+        init_at(temperature!(1000.0, K))
         expect_with(
-            Gas::N2 => 522.0,
-            Gas::O2 => 846.8133333333333,
-            Gas::CO2 => 403.0192638737864,
-            Gas::N2O => 297.99999990963187,
-            Gas::Pl => 2211.447402612144,
-            Gas::H2O => 134.0,
-            Gas::NO2 => 3.6584809783155587,
-            Gas::H2 => 119.6,
-            Gas::BZ => 77.65848097831555
+            Gas::N2O => 9.1,
+            Gas::N2 => 11.0
         )
-        expect_at(temperature!(1969362.373934752, K))
+        expect_at(temperature!(1000.0, K))
     );
+
+    #[test]
+    fn reaction_metadata_test() {
+        let ids: Vec<&str> = R::REACTIONS.iter().map(|r| r.id).collect();
+        assert_eq!(ids[0], "n2o_decomp");
+        assert!(ids.contains(&"miasma_sterilization"));
+
+        let fusion = R::FUSION;
+        assert_eq!(fusion.name, "Plasmic Fusion");
+        assert!(approx_eq!(f64, fusion.min_temperature(), 10000.0));
+        assert_eq!(fusion.max_temperature(), f64::INFINITY);
+        assert_eq!(
+            fusion.required_gases().collect::<Vec<_>>(),
+            vec![(Gas::H2, 1.0), (Gas::Pl, 250.0), (Gas::CO2, 250.0)]
+        );
+
+        let pluox = R::PLUOX_FORMATION;
+        assert!(approx_eq!(f64, pluox.max_temperature(), 273.15));
+        assert_eq!(pluox.products, &[Gas::PlOx]);
+    }
+
+    #[test]
+    fn diagnostics_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::CO2 => 120.0,
+                Gas::Pl => 300.0,
+                Gas::H2 => 5.0,
+            )
+            at(temperature!(8000.0, K))
+        );
+        let fusion = D::Diagnosis::of(&R::FUSION, &gm);
+
+        assert!(!fusion.can_react());
+        assert_eq!(
+            fusion.to_string(),
+            "fusion: temperature 8000/10000 K; CO2 120/250 mol"
+        );
+
+        let suppressed = D::Diagnosis::of(
+            &R::N2O_DECOMP,
+            &gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2O => 20.0,
+                    Gas::HNb => 10.0,
+                )
+                at(temperature!(2000.0, K))
+            ),
+        );
+
+        assert!(!suppressed.can_react());
+        assert_eq!(
+            suppressed.to_string(),
+            "n2o_decomp: suppressed by hyper-noblium (10 mol, threshold 5 mol)"
+        );
+    }
+
+    #[test]
+    fn diagnostics_report_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+        );
+        let report = D::report(&gm);
+
+        assert_eq!(report.lines().count(), R::REACTIONS.len());
+        assert!(report.contains("plasma_fire: can react"), "{}", report);
+        assert!(
+            report.contains(
+                "pluox_formation: CO2 0/0.01 mol; H2 0/0.01 mol; temperature 500/273.15 K at most"
+            ),
+            "{}",
+            report
+        );
+    }
+
+    #[test]
+    fn timeline_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+            in(2500.0)
+        );
+        let timeline = Timeline::record(gm, 10);
+
+        assert_eq!(timeline.steps.len(), 10);
+        assert_eq!(timeline.first_fire(), Some(1));
+        assert_eq!(timeline.first_reaction(), Some(1));
+        assert_eq!(
+            timeline.steps[0]
+                .output
+                .fired_reactions()
+                .map(|r| r.id)
+                .collect::<Vec<_>>(),
+            vec!["plasma_fire"]
+        );
+        assert!(
+            approx_eq!(
+                f64,
+                timeline
+                    .steps
+                    .iter()
+                    .map(|step| step.deltas[Gas::Pl])
+                    .sum::<f64>(),
+                timeline.last()[Gas::Pl] - gm[Gas::Pl],
+                epsilon = 0.0000001
+            ),
+            "Deltas don't add up"
+        );
+        assert!(
+            approx_eq!(
+                f64,
+                timeline.peak_temperature(),
+                timeline
+                    .steps
+                    .iter()
+                    .map(|step| step.temperature)
+                    .fold(0.0, f64::max)
+            ),
+            "Wrong peak temperature"
+        );
+        assert!(timeline.total_energy_released() > 0.0);
+        let endothermic = Timeline::record(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::N2 => 100.0,
+                    Gas::O2 => 100.0,
+                    Gas::PlOx => 5.0,
+                )
+                at(temperature!(50000.0, K))
+            ),
+            3,
+        );
+        assert!(endothermic.last().get_energy() < endothermic.initial.get_energy());
+        assert_eq!(endothermic.total_energy_released(), 0.0);
+
+        let csv = timeline.to_csv();
+        assert_eq!(csv.lines().count(), 11);
+        assert!(csv.starts_with("step,temperature,pressure,energy,N2,"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",plasma_fire"));
+
+        let json = timeline.to_json();
+        assert!(json.starts_with("{\"initial\":{"));
+        assert!(json.contains("\"first_fire\":1,"));
+        assert!(json.contains("\"fired\":[\"plasma_fire\"]"));
+    }
+
+    #[test]
+    fn simulation_resume_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+            in(2500.0)
+        );
+
+        let mut paused = Simulation::new(gm);
+        paused.run(3, &[]);
+        paused.run(7, &[]);
+
+        let mut straight = Simulation::new(gm);
+        straight.run(10, &[]);
+
+        assert_eq!(paused, straight);
+        assert_eq!(paused.steps, 10);
+        assert_eq!(paused.mixture, R::react_several(gm, 10)[9]);
+    }
+
+    #[test]
+    fn simulation_stop_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+            in(2500.0)
+        );
+        let threshold = gm.get_pressure() * 2.0;
+
+        let mut simulation = Simulation::new(gm);
+        let stopped_by = simulation.run(1000, &[StopCondition::PressureAbove(threshold)]);
+
+        assert_eq!(
+            stopped_by,
+            Some(Stop {
+                condition: StopCondition::PressureAbove(threshold),
+                step: simulation.steps
+            })
+        );
+        assert!(simulation.steps < 1000);
+        assert!(simulation.mixture.get_pressure() > threshold);
+        assert!(
+            R::react_several(gm, simulation.steps - 1)[simulation.steps - 2].get_pressure()
+                <= threshold
+        );
+    }
+
+    #[test]
+    fn react_several_until_test() {
+        let gm = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+                Gas::O2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+            in(2500.0)
+        );
+
+        let (timeline, stop) =
+            R::react_several_until(gm, 1000, &[StopCondition::GasDepleted(Gas::Pl)]);
+        let stop = stop.expect("Plasma never ran out");
+        assert_eq!(timeline.len(), stop.step);
+        assert!(timeline[stop.step - 1][Gas::Pl] < 0.01);
+        assert!(timeline[stop.step - 2][Gas::Pl] >= 0.01);
+
+        let (timeline, stop) = R::react_several_until(gm, 1000, &[StopCondition::Converged(1e-6)]);
+        assert!(stop.is_some(), "Burn never converged");
+        assert_eq!(
+            R::react_once(*timeline.last().unwrap()).temperature,
+            timeline.last().unwrap().temperature
+        );
+
+        let (timeline, stop) =
+            R::react_several_until(gm, 5, &[StopCondition::TemperatureBelow(100.0)]);
+        assert_eq!(timeline, R::react_several(gm, 5));
+        assert_eq!(stop, None);
+    }
+
+    #[test]
+    fn canister_release_test() {
+        let mut canister = Canister::new(gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 1000.0,
+            )
+            at(temperature!(20.0, C))
+        ));
+        let room = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        assert_eq!(canister.tick(&room), room, "Closed valve let gas out");
+
+        canister.valve_open = true;
+        let mut room = room;
+        for _ in 0..20 {
+            room = canister.tick(&room);
+        }
+
+        assert!(
+            approx_eq!(
+                f64,
+                room.get_pressure(),
+                canister.release_pressure,
+                epsilon = 0.01
+            ),
+            "Room is at {} kPa instead of the release pressure",
+            room.get_pressure()
+        );
+        assert!(
+            approx_eq!(
+                f64,
+                room[Gas::N2] + canister.air[Gas::N2],
+                1000.0,
+                epsilon = 0.0000001
+            ),
+            "Matter is not conserved"
+        );
+    }
+
+    #[test]
+    fn canister_holding_tank_test() {
+        let mut canister = Canister::new(gen_gas_mix_with_temp!(
+            with(
+                Gas::O2 => 1000.0,
+            )
+            at(temperature!(20.0, C))
+        ));
+        let empty_tank = gen_gas_mix_with_temp!(
+            with(
+                Gas::O2 => 0.0,
+            )
+            at(temperature!(20.0, C))
+            in(70.0)
+        );
+        let room = empty_tank;
+
+        canister.insert_tank(empty_tank);
+        canister.valve_open = true;
+        canister.set_release_pressure(100000.0);
+        assert!(approx_eq!(f64, canister.release_pressure, 1013.25));
+
+        for _ in 0..20 {
+            assert_eq!(
+                canister.tick(&room),
+                room,
+                "Gas went to the room instead of the tank"
+            );
+        }
+
+        let tank = canister.eject_tank().unwrap();
+        assert!(approx_eq!(
+            f64,
+            tank.get_pressure(),
+            1013.25,
+            epsilon = 0.01
+        ));
+    }
+
+    #[test]
+    fn canister_rupture_test() {
+        let mut canister = Canister::new(gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 50.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(20.0, C))
+        ));
+        let room = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        let room = canister.tick(&room);
+        assert!(!canister.ruptured, "Canister ruptured without a fire");
+
+        canister.air = canister.air + 1e9;
+        let room = canister.tick(&room);
+        assert!(canister.ruptured, "Canister survived the overpressure");
+        assert_eq!(canister.air.get_total_amount(), 0.0);
+        assert!(room[Gas::O2] > 400.0, "Contents didn't end up in the room");
+    }
+
+    #[test]
+    fn canister_connector_test() {
+        let mut canister = Canister::new(gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(20.0, C))
+        ));
+        let pipenet = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 300.0,
+            )
+            at(temperature!(20.0, C))
+            in(500.0)
+        );
+
+        let pipenet = canister.connect_to(&pipenet);
+        assert!(approx_eq!(f64, canister.air[Gas::N2], 200.0));
+        assert!(approx_eq!(f64, pipenet[Gas::N2], 100.0));
+        assert!(approx_eq!(
+            f64,
+            canister.air.get_pressure(),
+            pipenet.get_pressure()
+        ));
+    }
+
+    #[test]
+    fn scrubber_test() {
+        let room = gen_gas_mix_with_temp!(
+            with(
+                Gas::CO2 => 100.0,
+                Gas::N2 => 400.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let pipenet = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(20.0, C))
+            in(200.0)
+        );
+        let filter = enum_map! { Gas::CO2 => true, _ => false };
+
+        let (scrubbed, waste) =
+            Scrubber::new(ScrubberMode::Scrubbing, filter).process(&room, &pipenet);
+        assert!(approx_eq!(f64, scrubbed[Gas::CO2], 92.0));
+        assert!(approx_eq!(f64, scrubbed[Gas::N2], 400.0));
+        assert!(approx_eq!(f64, waste[Gas::CO2], 8.0));
+        assert!(approx_eq!(f64, waste[Gas::N2], 0.0));
+        assert!(approx_eq!(f64, scrubbed.volume, 2500.0));
+        assert!(approx_eq!(f64, waste.volume, 200.0));
+
+        let (siphoned, waste) =
+            Scrubber::new(ScrubberMode::Siphoning, filter).process(&room, &pipenet);
+        assert!(approx_eq!(f64, siphoned[Gas::CO2], 92.0));
+        assert!(approx_eq!(f64, siphoned[Gas::N2], 368.0));
+        assert!(approx_eq!(f64, waste[Gas::CO2], 8.0));
+        assert!(approx_eq!(f64, waste[Gas::N2], 32.0));
+
+        let clean_room = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 400.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        assert_eq!(
+            Scrubber::new(ScrubberMode::Scrubbing, filter).process(&clean_room, &pipenet),
+            (clean_room, pipenet),
+            "Scrubber took in air without any filtered gas"
+        );
+    }
+
+    #[test]
+    fn filter_test() {
+        let input = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+                Gas::Pl => 50.0,
+            )
+            at(temperature!(20.0, C))
+            in(200.0)
+        );
+        let empty = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(20.0, C))
+            in(200.0)
+        );
+        let filter = Filter::new(enum_map! { Gas::Pl => true, _ => false });
+
+        let (input_after, plasma, rest) = filter.process(&input, &empty, &empty);
+        assert!(approx_eq!(f64, input_after.get_total_amount(), 0.0));
+        assert!(approx_eq!(f64, plasma[Gas::Pl], 50.0));
+        assert!(approx_eq!(f64, plasma[Gas::N2], 0.0));
+        assert!(approx_eq!(f64, rest[Gas::N2], 100.0));
+        assert!(approx_eq!(f64, rest[Gas::Pl], 0.0));
+
+        let full = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 1000.0,
+            )
+            at(temperature!(20.0, C))
+            in(200.0)
+        );
+        let (input_after, plasma, rest) = filter.process(&input, &full, &empty);
+        assert!(
+            approx_eq!(f64, input_after[Gas::Pl], 50.0),
+            "Filtered gas didn't stay in the input"
+        );
+        assert_eq!(plasma, full);
+        assert!(approx_eq!(f64, rest[Gas::N2], 100.0));
+    }
+
+    #[test]
+    fn thermomachine_tier_test() {
+        let freezer = Thermomachine::new(ThermomachineMode::Freezer, 1);
+        assert!(approx_eq!(f64, freezer.min_temperature, 73.15));
+        assert!(approx_eq!(f64, freezer.heat_capacity, 5000.0));
+        assert!(approx_eq!(f64, freezer.target_temperature, 73.15));
+
+        let freezer = Thermomachine::new(ThermomachineMode::Freezer, 4);
+        assert!(approx_eq!(f64, freezer.min_temperature, 2.7));
+        assert!(approx_eq!(f64, freezer.heat_capacity, 245000.0));
+
+        let mut heater = Thermomachine::new(ThermomachineMode::Heater, 1);
+        assert!(approx_eq!(f64, heater.max_temperature, 573.15));
+        heater.set_target_temperature(10000.0);
+        assert!(approx_eq!(f64, heater.target_temperature, 573.15));
+    }
+
+    #[test]
+    fn thermomachine_process_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(20.0, C))
+            in(200.0)
+        );
+
+        let cooled = Thermomachine::new(ThermomachineMode::Freezer, 1).process(&air);
+        assert!(
+            approx_eq!(f64, cooled.temperature, 136.00714285714287),
+            "Wrong temperature: {}",
+            cooled.temperature
+        );
+        assert_eq!(cooled.gases, air.gases);
+    }
+
+    #[test]
+    fn heat_exchanger_test() {
+        let hot = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(500.0, K))
+        );
+        let cold = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 10.0,
+            )
+            at(temperature!(100.0, K))
+        );
+
+        let (hot_after, cold_after) = HeatExchanger::default().process(&hot, &cold);
+        assert!(approx_eq!(f64, hot_after.temperature, 300.0));
+        assert!(approx_eq!(f64, cold_after.temperature, 300.0));
+
+        let (hot_after, cold_after) = HeatExchanger { efficiency: 0.5 }.process(&hot, &cold);
+        assert!(approx_eq!(f64, hot_after.temperature, 400.0));
+        assert!(approx_eq!(f64, cold_after.temperature, 200.0));
+        assert!(approx_eq!(
+            f64,
+            hot_after.get_energy() + cold_after.get_energy(),
+            hot.get_energy() + cold.get_energy()
+        ));
+    }
+
+    #[test]
+    fn teg_test() {
+        let hot = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(1000.0, K))
+        );
+        let cold = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+            )
+            at(temperature!(100.0, K))
+        );
+
+        let tick = Teg::default().process(&hot, &cold);
+        assert!(approx_eq!(f64, tick.power, 585000.0));
+        assert!(approx_eq!(f64, tick.hot.temperature, 550.0));
+        assert!(approx_eq!(f64, tick.cold.temperature, 257.5));
+
+        let backwards = Teg::default().process(&cold, &hot);
+        assert_eq!(
+            backwards.power, 0.0,
+            "Generated power from a colder hot loop"
+        );
+        assert_eq!(backwards.hot, cold);
+    }
+
+    #[test]
+    fn teg_run_test() {
+        let burn_chamber = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 100.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(500.0, C))
+            in(2500.0)
+        );
+        let cold = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 1000.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        let ticks = Teg::default().run(&burn_chamber, &cold, 10);
+        assert_eq!(ticks.len(), 10);
+        assert!(ticks.iter().all(|tick| tick.power > 0.0));
+        assert!(ticks[9].hot[Gas::CO2] > 0.0, "The hot loop didn't burn");
+        assert!(ticks[9].cold.temperature > cold.temperature);
+    }
+
+    #[test]
+    fn supermatter_oxygen_test() {
+        let room = gen_gas_mix_with_temp!(
+            with(
+                Gas::O2 => 1000.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        let mut sm = Supermatter::default();
+        let (room_after, output) = sm.process(&room);
+
+        assert!(approx_eq!(f64, output.power, 53.65975599336093));
+        assert_eq!(output.damage, 0.0);
+        assert!(approx_eq!(
+            f64,
+            output.emitted[Gas::Pl],
+            0.03935139422783575
+        ));
+        assert!(approx_eq!(f64, output.emitted[Gas::O2], 0.1705115532463144));
+        assert!(approx_eq!(
+            f64,
+            room_after[Gas::O2],
+            1000.0 + output.emitted[Gas::O2]
+        ));
+        assert!(room_after.temperature > room.temperature);
+        assert_eq!(room_after.volume, room.volume);
+    }
+
+    #[test]
+    fn supermatter_nitrogen_test() {
+        let mut room = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 1000.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+
+        let mut sm = Supermatter::default();
+        for _ in 0..10 {
+            let (air, output) = sm.process(&room);
+            room = air;
+
+            assert_eq!(output.power, 0.0, "Nitrogen gave the crystal power");
+            assert_eq!(output.emitted.get_total_amount(), 0.0);
+        }
+        assert_eq!(sm.damage, 0.0);
+    }
+
+    #[test]
+    fn supermatter_high_power_test() {
+        let vacuum = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(2.7, K))
+            in(2500.0)
+        );
+
+        let mut sm = Supermatter {
+            power: 100_000.0,
+            ..Supermatter::default()
+        };
+        let (_, output) = sm.process(&vacuum);
+        assert!(approx_eq!(f64, output.power, 17_000.0, ulps = 4));
+
+        for _ in 0..100 {
+            let (_, output) = sm.process(&vacuum);
+            assert!(output.power >= 0.0, "Power decayed below zero");
+            assert!(output.power.is_finite());
+        }
+    }
+
+    #[test]
+    fn supermatter_damage_test() {
+        let vacuum = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 0.0,
+            )
+            at(temperature!(2.7, K))
+            in(2500.0)
+        );
+
+        let mut sm = Supermatter::default();
+        let (_, output) = sm.process(&vacuum);
+        assert!(approx_eq!(f64, output.damage, 0.1));
+
+        let mut sm = Supermatter::default();
+        let mut room = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 500.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(1000.0, C))
+            in(2500.0)
+        );
+        let mut damage = 0.0;
+        for _ in 0..10 {
+            let (air, output) = sm.process(&room);
+            room = air;
+
+            assert!(
+                output.damage - damage <= 1.8 + 1e-9,
+                "Took more damage in a tick than the hardcap"
+            );
+            damage = output.damage;
+        }
+        assert!(sm.damage > 0.0, "Hot plasma didn't damage the crystal");
+        assert!(sm.power > 0.0);
+    }
+
+    #[test]
+    fn explosion_range_test() {
+        let explosion = ExplosionRange::from_pressure((40.0 + 9.0 * 6.0) * 101.325);
+        assert!(approx_eq!(f64, explosion.range, 9.0, epsilon = 0.0000001));
+        assert_eq!(
+            (
+                explosion.devastation,
+                explosion.heavy,
+                explosion.light,
+                explosion.flash
+            ),
+            (2, 4, 9, 13)
+        );
+    }
+
+    #[test]
+    fn tank_rupture_test() {
+        let mut tank = Tank::new(gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 105.0,
+            )
+            at(temperature!(20.0, C))
+            in(70.0)
+        ));
+
+        for _ in 0..3 {
+            assert_eq!(tank.check_status(), None);
+        }
+        assert_eq!(tank.integrity, 0);
+        assert_eq!(tank.check_status(), Some(TankFailure::Rupture));
+        assert_eq!(tank.air.get_total_amount(), 0.0);
+    }
+
+    #[test]
+    fn transfer_valve_test() {
+        let plasma = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 60.0,
+            )
+            at(temperature!(1000.0, C))
+            in(70.0)
+        );
+        let oxygen = gen_gas_mix_with_temp!(
+            with(
+                Gas::O2 => 120.0,
+            )
+            at(temperature!(20.0, C))
+            in(70.0)
+        );
+
+        let result = T::transfer_valve(&plasma, &oxygen, 100);
+        assert_eq!(result.timeline.initial.volume, 140.0);
+        assert_eq!(result.failed_at, Some(result.timeline.steps.len()));
+        match result.failure {
+            Some(TankFailure::Explosion(explosion)) => assert!(explosion.light > 0),
+            failure => panic!("Tank didn't explode: {:?}", failure),
+        }
+
+        let inert = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 10.0,
+            )
+            at(temperature!(20.0, C))
+            in(70.0)
+        );
+        let result = T::transfer_valve(&inert, &inert, 10);
+        assert_eq!(result.failure, None);
+        assert_eq!(result.failed_at, None);
+        assert_eq!(result.timeline.steps.len(), 10);
+    }
+
+    #[test]
+    fn atmos_string_test() {
+        let air = GasMixture::from_atmos_string("o2=22;n2=82;TEMP=293.15", 2500.0).unwrap();
+        assert_eq!(air[Gas::O2], 22.0);
+        assert_eq!(air[Gas::N2], 82.0);
+        assert_eq!(air.temperature, 293.15);
+        assert_eq!(air.volume, 2500.0);
+        assert_eq!(
+            GasMixture::from_atmos_string(&air.to_atmos_string(), 2500.0),
+            Ok(air)
+        );
+
+        let airless = GasMixture::from_atmos_string("TEMP=2.7", 2500.0).unwrap();
+        assert_eq!(airless.get_total_amount(), 0.0);
+        assert_eq!(
+            GasMixture::from_atmos_string("plasma=10", 70.0)
+                .unwrap()
+                .temperature,
+            293.15
+        );
+
+        assert_eq!(
+            GasMixture::from_atmos_string("o2=22;foo=1", 2500.0),
+            Err(AtmosStringError::UnknownGas("foo".to_owned()))
+        );
+        assert_eq!(
+            GasMixture::from_atmos_string("o2=lots", 2500.0),
+            Err(AtmosStringError::InvalidNumber("lots".to_owned()))
+        );
+        assert_eq!(
+            GasMixture::from_atmos_string("o2", 2500.0),
+            Err(AtmosStringError::MissingValue("o2".to_owned()))
+        );
+    }
+
+    #[test]
+    fn grid_conservation_test() {
+        let mut grid = Grid::new(4, 4, Turf::open("TEMP=293.15").unwrap());
+        grid.set(0, 0, Turf::open("o2=1000;plasma=100;TEMP=500").unwrap());
+        grid.set(1, 1, Turf::Closed);
+        let energy = |grid: &Grid| -> f64 {
+            grid.turfs
+                .iter()
+                .filter_map(Turf::air)
+                .map(|air| air.get_energy())
+                .sum()
+        };
+        let initial_energy = energy(&grid);
+
+        for _ in 0..50 {
+            grid.tick();
+        }
+
+        assert!(approx_eq!(
+            f64,
+            grid.total_moles(),
+            1100.0,
+            epsilon = 0.0000001
+        ));
+        assert!(approx_eq!(
+            f64,
+            energy(&grid),
+            initial_energy,
+            epsilon = 0.001
+        ));
+        assert!(grid.get(3, 3).air().unwrap()[Gas::O2] > 0.0);
+        assert_eq!(grid.get(1, 1).air(), None);
+    }
+
+    #[test]
+    fn grid_closed_turf_test() {
+        let mut grid = Grid::new(3, 1, Turf::Closed);
+        grid.set(0, 0, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        grid.set(2, 0, Turf::open(C::AIRLESS_ATMOS).unwrap());
+
+        for _ in 0..10 {
+            grid.tick();
+        }
+
+        assert_eq!(grid.get(2, 0).air().unwrap().get_total_amount(), 0.0);
+        assert_eq!(grid.get(0, 0).air().unwrap()[Gas::O2], 22.0);
+    }
+
+    #[test]
+    fn grid_space_test() {
+        let mut grid = Grid::new(3, 1, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        grid.set(2, 0, Turf::Space);
+
+        grid.tick();
+        let after_one = grid.total_moles();
+        assert!(after_one < 208.0, "Space didn't take in any gas");
+
+        for _ in 0..500 {
+            grid.tick();
+        }
+
+        // Space drains the turfs by sharing until they settle, without breakdowns emptying them
+        assert!(grid.total_moles() < 0.05 * after_one);
+        assert!(grid.total_moles() > 4.0 * C::MINIMUM_MOLES_DELTA_TO_MOVE);
+        assert_eq!(grid.get(2, 0), &Turf::Space);
+        assert!(grid
+            .turfs
+            .iter()
+            .filter_map(Turf::air)
+            .all(|air| air.temperature >= 2.7));
+    }
+
+    #[test]
+    fn grid_all_consuming_space_test() {
+        let mut grid = Grid::new(3, 1, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        grid.set(2, 0, Turf::Space);
+        grid.space_is_all_consuming = true;
+
+        for _ in 0..500 {
+            grid.tick();
+        }
+
+        assert!(grid.total_moles() < 4.0 * C::MINIMUM_MOLES_DELTA_TO_MOVE);
+        assert_eq!(grid.get(2, 0), &Turf::Space);
+    }
+
+    #[test]
+    fn grid_planetary_test() {
+        let mut grid = Grid::new(2, 1, Turf::planetary(C::LAVALAND_DEFAULT_ATMOS).unwrap());
+        grid.set(1, 0, Turf::open(C::AIRLESS_ATMOS).unwrap());
+
+        for _ in 0..200 {
+            grid.tick();
+        }
+
+        let lavaland = GasMixture::from_atmos_string(C::LAVALAND_DEFAULT_ATMOS, 2500.0).unwrap();
+        for turf in &grid.turfs {
+            let air = turf.air().unwrap();
+            assert!(approx_eq!(
+                f64,
+                air[Gas::O2],
+                lavaland[Gas::O2],
+                epsilon = C::MINIMUM_MOLES_DELTA_TO_MOVE
+            ));
+            assert!(approx_eq!(
+                f64,
+                air[Gas::N2],
+                lavaland[Gas::N2],
+                epsilon = C::MINIMUM_MOLES_DELTA_TO_MOVE
+            ));
+            assert!(approx_eq!(
+                f64,
+                air.temperature,
+                lavaland.temperature,
+                epsilon = C::MINIMUM_TEMPERATURE_DELTA_TO_SUSPEND
+            ));
+        }
+    }
+
+    #[test]
+    fn grid_immutable_test() {
+        let hot = GasMixture::from_atmos_string("n2=100;TEMP=1000", 2500.0).unwrap();
+        let mut grid = Grid::new(2, 1, Turf::Immutable(hot));
+        grid.set(1, 0, Turf::open("n2=10;TEMP=293.15").unwrap());
+
+        for _ in 0..200 {
+            grid.tick();
+        }
+
+        assert_eq!(grid.get(0, 0), &Turf::Immutable(hot));
+        let air = grid.get(1, 0).air().unwrap();
+        assert!(approx_eq!(
+            f64,
+            air[Gas::N2],
+            100.0,
+            epsilon = C::MINIMUM_MOLES_DELTA_TO_MOVE
+        ));
+        assert!(approx_eq!(
+            f64,
+            air.temperature,
+            1000.0,
+            epsilon = C::MINIMUM_TEMPERATURE_DELTA_TO_SUSPEND
+        ));
+    }
+
+    #[test]
+    fn grid_active_turfs_test() {
+        let mut grid = Grid::new(10, 10, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+
+        let settled = grid.tick();
+        assert_eq!(settled.active_turfs, 100);
+        assert_eq!(settled.shares, 0);
+        assert_eq!(grid.active_count(), 0, "Identical turfs didn't fall asleep");
+        assert_eq!(grid.tick().active_turfs, 0);
+
+        grid.set(5, 5, Turf::open("plasma=1000;TEMP=293.15").unwrap());
+        let initial_moles = grid.total_moles();
+        let stats = grid.run(200);
+
+        assert_eq!(stats.len(), 200);
+        assert_eq!(stats[0].active_turfs, 5);
+        assert!(stats.iter().any(|tick| tick.excited_groups > 0));
+        assert!(stats.iter().map(|tick| tick.active_turfs).max().unwrap() > 5);
+        assert_eq!(stats[199].active_turfs, 0, "The grid never settled down");
+        assert_eq!(stats[199].excited_groups, 0);
+        assert!(approx_eq!(
+            f64,
+            grid.total_moles(),
+            initial_moles,
+            epsilon = 0.0000001
+        ));
+        assert!(
+            grid.get(0, 0).air().unwrap()[Gas::Pl] > 0.0,
+            "Plasma didn't spread across the room"
+        );
+    }
+
+    #[test]
+    fn grid_asleep_test() {
+        let mut grid = Grid::new(300, 300, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        assert_eq!(grid.tick().active_turfs, 90000);
+        assert!(grid.active.is_empty());
+
+        assert_eq!(
+            grid.tick(),
+            TickStats::default(),
+            "A sleeping grid still did work"
+        );
+
+        grid.set(150, 150, Turf::open("n2=1000;TEMP=293.15").unwrap());
+        let stats = grid.tick();
+        assert_eq!(stats.active_turfs, 5);
+        assert!(
+            stats.mixtures_read <= 5 * 5,
+            "Turfs far from the change were read"
+        );
+    }
+
+    #[test]
+    fn equalize_room_test() {
+        let mut grid = Grid::new(6, 3, Turf::open("n2=10;TEMP=293.15").unwrap());
+        for y in 0..3 {
+            grid.set(0, y, Turf::open("n2=1000;TEMP=293.15").unwrap());
+            grid.set(5, y, Turf::Closed);
+        }
+        let initial_moles = grid.total_moles();
+
+        let equalization = grid.equalize(0);
+
+        assert!(!equalization.decompressed);
+        assert_eq!(equalization.turfs.len(), 15);
+        assert!(equalization.moved_moles() > 0.0);
+        assert!(approx_eq!(
+            f64,
+            grid.total_moles(),
+            initial_moles,
+            epsilon = 0.0000001
+        ));
+        for &turf in &equalization.turfs {
+            assert!(approx_eq!(
+                f64,
+                grid.turfs[turf].air().unwrap().get_total_amount(),
+                initial_moles / 15.0,
+                epsilon = 0.0000001
+            ));
+        }
+
+        let vectors = grid.pressure_vectors(&equalization.transfers);
+        assert!(
+            vectors[0].0 > 0.0,
+            "Gas didn't flow away from the high pressure side"
+        );
+        assert!(vectors[4].0 == 0.0 && vectors[4].1 == 0.0);
+        assert!(
+            grid.equalize_active().is_empty(),
+            "The room didn't settle down"
+        );
+    }
+
+    #[test]
+    fn equalize_decompression_test() {
+        let mut grid = Grid::new(5, 3, Turf::Closed);
+        for x in 0..4 {
+            grid.set(x, 1, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        }
+        grid.set(4, 1, Turf::Space);
+        let initial_moles = grid.total_moles();
+
+        let equalizations = grid.equalize_active();
+
+        assert_eq!(equalizations.len(), 1);
+        let equalization = &equalizations[0];
+        assert!(equalization.decompressed);
+        assert!(grid.total_moles() < C::MINIMUM_MOLES_DELTA_TO_MOVE);
+        let into_space: f64 = equalization
+            .transfers
+            .iter()
+            .filter(|transfer| transfer.to == 9)
+            .map(|transfer| transfer.moles)
+            .sum();
+        assert!(approx_eq!(
+            f64,
+            into_space,
+            initial_moles,
+            epsilon = 0.0000001
+        ));
+
+        let vectors = grid.pressure_vectors(&equalization.transfers);
+        for x in 0..4 {
+            assert!(vectors[5 + x].0 > 0.0, "Gas didn't flow towards the breach");
+            assert!(vectors[5 + x].1 == 0.0);
+        }
+        assert!(vectors[8].0 > vectors[5].0);
+    }
+
+    #[test]
+    fn gas_index_test() {
+        assert_eq!(Gas::from_index(0), Some(Gas::N2));
+        assert_eq!(Gas::from_index(12), Some(Gas::Miasma));
+        assert_eq!(Gas::from_index(F::gas_count()), None);
+        assert_eq!(Gas::from_index(usize::MAX), None);
+    }
+
+    #[test]
+    fn gas_count_ffi_test() {
+        assert_eq!(F::gas_count(), 13);
+        assert_eq!(
+            std::mem::size_of::<F::GasMixtureFFI>(),
+            (F::gas_count() + 2) * std::mem::size_of::<f64>()
+        );
+    }
+
+    #[test]
+    fn equalize_ffi_test() {
+        let mut grid = Grid::new(5, 3, Turf::Closed);
+        for x in 0..4 {
+            grid.set(x, 1, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        }
+        grid.set(4, 1, Turf::Space);
+        let expected: Vec<Transfer> = grid
+            .clone()
+            .equalize_active()
+            .into_iter()
+            .flat_map(|equalization| equalization.transfers)
+            .collect();
+
+        let mut vectors = vec![0.0; 30];
+        let mut transfers = vec![
+            Transfer {
+                from: 0,
+                to: 0,
+                moles: 0.0
+            };
+            30
+        ];
+        let handle = Box::into_raw(Box::new(grid.clone()));
+        let count = unsafe {
+            F::grid_equalize_with_transfers(
+                handle,
+                vectors.as_mut_ptr(),
+                transfers.as_mut_ptr(),
+                transfers.len(),
+            )
+        };
+        assert_eq!(count, expected.len());
+        assert_eq!(&transfers[..count], expected.as_slice());
+        assert!(vectors[2 * 8] > 0.0, "Gas didn't flow towards the breach");
+
+        let mut vectors = vec![0.0; 30];
+        let handle_without_transfers = Box::into_raw(Box::new(grid));
+        let count = unsafe {
+            F::grid_equalize_with_transfers(
+                handle_without_transfers,
+                vectors.as_mut_ptr(),
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        assert_eq!(count, expected.len());
+        assert!(
+            vectors[2 * 8] > 0.0,
+            "Didn't equalize without a transfer buffer"
+        );
+
+        unsafe {
+            F::grid_destroy(handle);
+            F::grid_destroy(handle_without_transfers);
+        }
+    }
+
+    #[test]
+    fn dmm_classic_test() {
+        let map = r#"//MAP CONVERTED BY dmm2tgm.py THIS HEADER COMMENT PREVENTS RECONVERSION, DO NOT REMOVE
+"a" = (/turf/closed/wall/r_wall,/area/station)
+"b" = (/obj/item/wrench,/turf/open/floor/iron,/area/station)
+"c" = (/turf/open/floor/iron{initial_gas_mix = "plasma=100;TEMP=500"; name = "hot, \"really\" hot"},/area/station)
+"d" = (/obj/machinery/door/airlock/external,/turf/open/floor/iron,/area/station)
+"e" = (/obj/effect/spawner/structure/window/reinforced,/turf/open/floor/plating,/area/station)
+"f" = (/turf/open/space/basic,/area/space)
+"g" = (/obj/machinery/door/firedoor,/turf/open/floor/plating/airless,/area/station)
+
+(1,1,1) = {"
+aaaaf
+abcdf
+aegbf
+"}
+"#;
+        let loaded = M::load_dmm(map, &M::default_rules(), 1).unwrap();
+        let grid = &loaded.grid;
+
+        assert_eq!((grid.width, grid.height), (5, 3));
+        assert_eq!(grid.get(0, 0), &Turf::Closed);
+        assert_eq!(loaded.kinds[grid.index(0, 0)], Some(TileKind::Wall));
+        assert_eq!(
+            grid.get(1, 1),
+            &Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap()
+        );
+        assert_eq!(grid.get(2, 1), &Turf::open("plasma=100;TEMP=500").unwrap());
+        assert_eq!(loaded.kinds[grid.index(3, 1)], Some(TileKind::Door));
+        assert_eq!(grid.get(3, 1), &Turf::Closed);
+        assert_eq!(loaded.kinds[grid.index(1, 2)], Some(TileKind::Window));
+        assert_eq!(grid.get(2, 2), &Turf::open(C::AIRLESS_ATMOS).unwrap());
+        assert_eq!(grid.get(4, 0), &Turf::Space);
+        assert_eq!(grid.neighbours(grid.index(1, 1)), vec![grid.index(2, 1)]);
+
+        let mut rules = M::default_rules();
+        rules.push(DmmRule::new("/obj/item/wrench", TileKind::Wall));
+        let loaded = M::load_dmm(map, &rules, 1).unwrap();
+        assert_eq!(loaded.grid.get(1, 1), &Turf::Closed);
+
+        assert_eq!(
+            M::load_dmm(map, &M::default_rules(), 2),
+            Err(DmmError::MissingLevel(2))
+        );
+        assert_eq!(
+            M::load_dmm(&map.replace("aegbf", "aeghf"), &M::default_rules(), 1),
+            Err(DmmError::UnknownKey("h".to_owned()))
+        );
+    }
+
+    #[test]
+    fn dmm_malformed_test() {
+        let map = r#"
+"a" = (/turf/closed/wall,/area/station)
+"b" = (/turf/open/floor/iron{initial_gas_mix = "n2=100;TEMP=300"; name = "{}"},/area/station)
+
+(1,1,1) = {"
+aba
+"}
+"#;
+        let loaded = M::load_dmm(map, &M::default_rules(), 1).unwrap();
+        assert_eq!(
+            loaded.grid.get(1, 0),
+            &Turf::open("n2=100;TEMP=300").unwrap()
+        );
+
+        assert!(matches!(
+            M::load_dmm(&map.replace("aba", "aéa"), &M::default_rules(), 1),
+            Err(DmmError::Syntax(_))
+        ));
+        assert!(matches!(
+            M::load_dmm(&map.replace("\"{}\"}", "\"{}\"} x"), &M::default_rules(), 1),
+            Err(DmmError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn dmm_tgm_test() {
+        let map =
+            "//MAP CONVERTED BY dmm2tgm.py THIS HEADER COMMENT PREVENTS RECONVERSION, DO NOT REMOVE
+\"aa\" = (
+/turf/open/misc/asteroid/basalt/lava_land_surface{
+\tinitial_gas_mix = \"o2=14;n2=23;TEMP=300\";
+\tplanetary_atmos = 1
+\t},
+/area/lavaland/surface/outdoors)
+\"ab\" = (
+/turf/closed/mineral/volcanic,
+/area/lavaland/surface/outdoors)
+
+(1,1,1) = {\"
+aa
+ab
+\"}
+(2,1,1) = {\"
+ab
+aa
+\"}
+";
+        let loaded = M::load_dmm(map, &M::default_rules(), 1).unwrap();
+        let grid = &loaded.grid;
+
+        assert_eq!((grid.width, grid.height), (2, 2));
+        assert_eq!(
+            grid.get(0, 0),
+            &Turf::planetary(C::LAVALAND_DEFAULT_ATMOS).unwrap()
+        );
+        assert_eq!(grid.get(1, 0), &Turf::Closed);
+        assert_eq!(grid.get(0, 1), &Turf::Closed);
+        assert_eq!(loaded.kinds[grid.index(1, 1)], Some(TileKind::Floor));
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let mut grid = Grid::new(6, 6, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+        grid.set(2, 2, Turf::open("plasma=500;o2=500;TEMP=600").unwrap());
+        grid.set(0, 0, Turf::Space);
+        grid.set(5, 5, Turf::planetary(C::LAVALAND_DEFAULT_ATMOS).unwrap());
+        grid.set(3, 0, Turf::Closed);
+        grid.space_is_all_consuming = true;
+        grid.run(5);
+
+        let pipenet = gen_gas_mix_with_temp!(
+            with(
+                Gas::N2 => 100.0,
+                Gas::Pl => 10.0,
+            )
+            at(C::T20C)
+            in(C::CELL_VOLUME)
+        );
+        let mut canister = Canister::new(pipenet);
+        canister.insert_tank(pipenet);
+        let mut supermatter = Supermatter::default();
+        supermatter.process(&pipenet);
+        let mut simulation = Simulation::seeded(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::Pl => 100.0,
+                    Gas::O2 => 200.0,
+                )
+                at(1000.0)
+                in(C::CELL_VOLUME)
+            ),
+            11,
+        );
+        simulation.step();
+        let mut unseeded = Simulation::new(simulation.mixture);
+        unseeded.step();
+
+        let machines = vec![
+            Machine::Canister(canister),
+            Machine::Scrubber(Scrubber::new(
+                ScrubberMode::Siphoning,
+                enum_map! { Gas::CO2 => true, _ => false },
+            )),
+            Machine::Filter(Filter::new(enum_map! { Gas::Pl => true, _ => false })),
+            Machine::Thermomachine(Thermomachine::new(ThermomachineMode::Freezer, 3)),
+            Machine::HeatExchanger(HeatExchanger::default()),
+            Machine::Teg(Teg::default()),
+            Machine::Supermatter(supermatter),
+            Machine::Tank(Tank::new(pipenet)),
+        ];
+        let snapshot = Snapshot {
+            grid: Some(grid),
+            pipenets: vec![pipenet, GasMixture::zero()],
+            machines: machines
+                .into_iter()
+                .enumerate()
+                .map(|(i, machine)| PlacedMachine {
+                    machine,
+                    turf: if i % 2 == 0 { Some(i) } else { None },
+                    pipenets: vec![i % 2, 1],
+                })
+                .collect(),
+            simulations: vec![unseeded, simulation],
+        };
+
+        let bytes = snapshot.to_bytes();
+        let mut restored = Snapshot::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, snapshot);
+        assert!(restored.diff(&snapshot).is_empty());
+
+        let path = std::env::temp_dir().join("tg_atmos_sim_snapshot_test.bin");
+        snapshot.save(&path).unwrap();
+        assert_eq!(Snapshot::load(&path), Ok(snapshot.clone()));
+        std::fs::remove_file(&path).unwrap();
+
+        let mut original = snapshot.clone();
+        let original_grid = original.grid.as_mut().unwrap();
+        let restored_grid = restored.grid.as_mut().unwrap();
+        assert_eq!(original_grid.run(20), restored_grid.run(20));
+        assert_eq!(
+            original, restored,
+            "The restored grid didn't resume exactly"
+        );
+
+        let differences = snapshot.diff(&original);
+        assert!(!differences.is_empty());
+        assert!(differences
+            .iter()
+            .all(|difference| difference.path.starts_with("grid.")));
+        restored.pipenets[1].temperature = 100.0;
+        assert!(original
+            .diff(&restored)
+            .iter()
+            .any(|difference| difference.path == "pipenets[1].temperature"
+                && difference.before == "0.0"
+                && difference.after == "100.0"));
+        restored.simulations[1].rng = None;
+        assert!(original
+            .diff(&restored)
+            .iter()
+            .any(|difference| difference.path == "simulations[1].rng"));
+
+        let mut nan = original.clone();
+        nan.pipenets[0].temperature = f64::NAN;
+        assert!(nan.diff(&nan.clone()).is_empty());
+        let mut negative_zero = original.clone();
+        negative_zero.pipenets[0].temperature = -0.0;
+        let mut positive_zero = original.clone();
+        positive_zero.pipenets[0].temperature = 0.0;
+        assert_eq!(negative_zero.diff(&positive_zero).len(), 1);
+
+        assert_eq!(
+            Snapshot::from_bytes(b"nope"),
+            Err(SnapshotError::NotASnapshot)
+        );
+        assert_eq!(
+            Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        );
+        let mut future = bytes.clone();
+        future[4..8].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Snapshot::from_bytes(&future),
+            Err(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn snapshot_ffi_test() {
+        let path = std::env::temp_dir().join("tg_atmos_sim_snapshot_ffi_test.bin");
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        let mut simulation = Simulation::seeded(
+            gen_gas_mix_with_temp!(
+                with(
+                    Gas::H2 => 100.0,
+                    Gas::O2 => 500.0,
+                )
+                at(500.0)
+                in(C::CELL_VOLUME)
+            ),
+            3,
+        );
+        simulation.step();
+
+        unsafe {
+            assert!(F::simulation_save(&simulation, c_path.as_ptr()));
+            let restored = F::simulation_restore(c_path.as_ptr());
+            assert!(!restored.is_null());
+            assert_eq!(*restored, simulation);
+            F::simulation_step(restored, 5);
+            simulation.run(5, &[]);
+            assert_eq!(
+                *restored, simulation,
+                "The restored simulation didn't resume exactly"
+            );
+            F::simulation_destroy(restored);
+
+            let grid = Grid::new(2, 2, Turf::open(C::OPENTURF_DEFAULT_ATMOS).unwrap());
+            assert!(F::grid_save(&grid, c_path.as_ptr()));
+            assert!(F::simulation_restore(c_path.as_ptr()).is_null());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rng_test() {
+        let rolls = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..100).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(rolls(42), rolls(42));
+        assert_ne!(rolls(42), rolls(43));
+
+        let mut rng = Rng::new(0);
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.next_f64())));
+        assert!((0..1000).all(|_| !rng.prob(0.0) && rng.prob(100.0)));
+        let passed = (0..10000).filter(|_| rng.prob(10.0)).count();
+        assert!(
+            (900..1100).contains(&passed),
+            "prob(10) passed {} times out of 10000",
+            passed
+        );
+
+        assert!(rng::prob(None, 0.1));
+        assert!(!rng::prob(None, 0.0));
+    }
+
+    #[test]
+    fn seeded_reactions_test() {
+        let burning = gen_gas_mix_with_temp!(
+            with(
+                Gas::H2 => 100.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(500., K))
+        );
+        let pulses = |seed: u64| {
+            (0..1000)
+                .filter(|&i| {
+                    let mut rng = Rng::new(seed + i);
+                    R::react_once_rolling(burning, &mut rng).1.radiation > 0.0
+                })
+                .count()
+        };
+        let pulsed = pulses(0);
+        assert_eq!(pulsed, pulses(0));
+        assert!(
+            (50..150).contains(&pulsed),
+            "Tritium pulsed on {} burns out of 1000",
+            pulsed
+        );
+        assert!(R::react_once_with_output(burning).1.radiation > 0.0);
+
+        let run = |seed| {
+            let mut simulation = Simulation::seeded(burning, seed);
+            (0..20)
+                .map(|_| {
+                    simulation.step();
+                    simulation.output
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_eq!(Simulation::new(burning).rng, None);
+    }
+
+    #[test]
+    fn seeded_fire_spread_test() {
+        let air = gen_gas_mix_with_temp!(
+            with(
+                Gas::Pl => 50.0,
+                Gas::O2 => 500.0,
+            )
+            at(temperature!(20.0, C))
+            in(2500.0)
+        );
+        let burn = |seed, spread_chance| {
+            let mut area = FireArea::new(
+                vec![air; 5],
+                vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]],
+            );
+            area.spread_chance = spread_chance;
+            area.rng = Some(Rng::new(seed));
+            area.expose(0, 1000.0, 5.0);
+            for _ in 0..20 {
+                area.tick();
+            }
+
+            area
+        };
+
+        assert_eq!(burn(3, 50.0), burn(3, 50.0));
+        let contained = burn(3, 0.0);
+        assert!(contained.hotspots[0].is_some());
+        assert_eq!(
+            contained.burning_count(),
+            1,
+            "Fire spread without a chance to"
+        );
+    }
+
+    #[test]
+    fn golden_regression_traces_test() {
+        let reports = G::replay_traces(include_str!("../traces/regression.trace")).unwrap();
+        assert!(reports.len() >= 10);
+        for report in &reports {
+            println!("{}", report);
+            assert!(report.passed(), "{} deviated from its trace", report.name);
+        }
+    }
+
+    #[test]
+    fn golden_trace_format_test() {
+        let input = gen_gas_mix_with_temp!(with(Gas::Pl => 10., Gas::O2 => 100.,) at(500.) in(C::CELL_VOLUME));
+        let mut trace = Trace::record("plasma_fire", "test", input, 3);
+        trace.reaction = Some("plasma_fire".to_owned());
+        trace.compared = Some(vec![Gas::Pl, Gas::O2, Gas::CO2]);
+        trace.tolerance.moles = 0.01;
+
+        let text = G::write_traces(&[trace.clone()]);
+        assert_eq!(G::parse_traces(&text).unwrap(), vec![trace]);
+
+        assert_eq!(
+            G::parse_traces("input o2=1;TEMP=300"),
+            Err(TraceError::UnexpectedLine(1))
+        );
+        assert_eq!(
+            G::parse_traces("# comment\ntrace a\nsauce 1"),
+            Err(TraceError::UnexpectedLine(3))
+        );
+        assert_eq!(
+            G::parse_traces("trace a\nvolume lots"),
+            Err(TraceError::InvalidNumber(2))
+        );
+        assert_eq!(
+            G::parse_traces("trace a\nreaction bonfire"),
+            Err(TraceError::UnknownReaction(2))
+        );
+        assert_eq!(
+            G::parse_traces("trace a\ncompare o2;air"),
+            Err(TraceError::UnknownGas(2))
+        );
+        assert_eq!(
+            G::parse_traces("trace a\ninput o2=1;TEMP=300\ntrace b"),
+            Err(TraceError::Incomplete("a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn golden_trace_deviation_test() {
+        let input = gen_gas_mix_with_temp!(with(Gas::H2 => 100., Gas::O2 => 500.,) at(500.) in(C::CELL_VOLUME));
+        let mut trace = Trace::record("trit_fire", "test", input, 2);
+        trace.outputs[1].temperature *= 1.01;
+
+        let report = trace.replay();
+        println!("{}", report);
+        assert!(!report.passed());
+
+        let failed: Vec<_> = report
+            .deviations
+            .iter()
+            .filter(|deviation| !deviation.within_tolerance)
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!((failed[0].step, failed[0].quantity.as_str()), (2, "TEMP"));
+        assert!(report
+            .worst_errors()
+            .iter()
+            .any(|&(quantity, error)| quantity == "TEMP"
+                && approx_eq!(f64, error, 0.01 / 1.01, epsilon = 1e-9)));
+
+        let mut partial = Trace::record("trit_fire", "test", input, 1);
+        partial.outputs[0].gases.0[Gas::H2O] += 1.0;
+        partial.compared = Some(vec![Gas::H2, Gas::O2]);
+        assert!(
+            partial.replay().passed(),
+            "A gas that isn't compared deviated"
+        );
+        partial.compared = Some(vec![Gas::H2O]);
+        assert!(!partial.replay().passed());
+    }
 }
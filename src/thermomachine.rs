@@ -0,0 +1,100 @@
+use crate::constants as C;
+use crate::GasMixture;
+
+/// Matter bins and micro lasers in a thermomachine, their ratings add up
+const PARTS_PER_KIND: f64 = 2.;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ThermomachineMode {
+    Freezer,
+    Heater,
+}
+
+/// A freezer or heater pulling the mixture on its port towards a target temperature
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Thermomachine {
+    pub mode: ThermomachineMode,
+    pub target_temperature: f64,
+    /// Heat capacity of the heat sink the mixture is equalized with every tick
+    pub heat_capacity: f64,
+    pub min_temperature: f64,
+    pub max_temperature: f64,
+}
+
+impl Thermomachine {
+    /// A thermomachine built with stock parts of `tier`, from 1 to 4, set to its coldest or hottest temperature
+    pub fn new(mode: ThermomachineMode, tier: u8) -> Self {
+        let rating = f64::from(tier.clamp(1, 4)) * PARTS_PER_KIND;
+        let (min_temperature, max_temperature) = match mode {
+            ThermomachineMode::Freezer => (
+                (C::T0C
+                    - (C::FREEZER_BASE_MIN_TEMPERATURE + rating * C::THERMOMACHINE_LASER_COOLING))
+                    .max(C::TCMB),
+                C::T20C,
+            ),
+            ThermomachineMode::Heater => {
+                (C::T20C, C::T20C + C::HEATER_BASE_MAX_TEMPERATURE * rating)
+            }
+        };
+
+        Self {
+            mode,
+            target_temperature: match mode {
+                ThermomachineMode::Freezer => min_temperature,
+                ThermomachineMode::Heater => max_temperature,
+            },
+            heat_capacity: C::THERMOMACHINE_HEAT_CAPACITY_SCALE * (rating - 1.).powi(2),
+            min_temperature,
+            max_temperature,
+        }
+    }
+
+    pub fn set_target_temperature(&mut self, temperature: f64) {
+        self.target_temperature = temperature.clamp(self.min_temperature, self.max_temperature);
+    }
+
+    /// Heat or cool `air` for a tick
+    pub fn process(&self, air: &GasMixture) -> GasMixture {
+        let air_heat_capacity = air.get_heat_cap();
+        let combined_heat_capacity = self.heat_capacity + air_heat_capacity;
+        if combined_heat_capacity <= 0. {
+            return *air;
+        }
+
+        GasMixture {
+            temperature: (self.heat_capacity * self.target_temperature + air.get_energy())
+                / combined_heat_capacity,
+            ..*air
+        }
+    }
+}
+
+/// A pair of heat exchangers moving heat between two otherwise separate mixtures
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HeatExchanger {
+    /// Share of the temperature difference closed every tick, 1 equalizes the mixtures fully like the in-game exchanger
+    pub efficiency: f64,
+}
+
+impl Default for HeatExchanger {
+    fn default() -> Self {
+        Self { efficiency: 1. }
+    }
+}
+
+impl HeatExchanger {
+    /// Exchange heat between `a` and `b` for a tick, returning both mixtures afterwards
+    pub fn process(&self, a: &GasMixture, b: &GasMixture) -> (GasMixture, GasMixture) {
+        let a_heat_capacity = a.get_heat_cap();
+        let b_heat_capacity = b.get_heat_cap();
+        if a_heat_capacity <= 0. || b_heat_capacity <= 0. {
+            return (*a, *b);
+        }
+
+        let equalized = (a.get_energy() + b.get_energy()) / (a_heat_capacity + b_heat_capacity);
+        let transferred =
+            (equalized - a.temperature) * a_heat_capacity * self.efficiency.clamp(0., 1.);
+
+        (*a + transferred, *b + -transferred)
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use crate::reactions::{self as R, ReactionOutput};
+use crate::{GasMixture, GasVec};
+
+/// A single reaction step of a timeline
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Step {
+    pub mixture: GasMixture,
+    pub temperature: f64,
+    pub pressure: f64,
+    pub energy: f64,
+    /// Change in the amount of each gas compared to the previous step
+    pub deltas: GasVec,
+    pub output: ReactionOutput,
+}
+
+/// History of a mixture reacting step by step.
+/// Steps are numbered from 1, step 0 being the initial mixture.
+/// Steps are reacted without a generator, so every chance gate passes whenever its chance is above 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline {
+    pub initial: GasMixture,
+    pub steps: Vec<Step>,
+}
+
+impl Timeline {
+    pub fn new(initial: GasMixture) -> Self {
+        Self {
+            initial,
+            steps: Vec::new(),
+        }
+    }
+
+    /// React `gm` `times` times and record every step
+    pub fn record(gm: GasMixture, times: usize) -> Self {
+        let mut timeline = Self::new(gm);
+        for _ in 1..=times {
+            timeline.step();
+        }
+
+        timeline
+    }
+
+    pub fn last(&self) -> GasMixture {
+        self.steps.last().map_or(self.initial, |step| step.mixture)
+    }
+
+    /// React the last mixture once and record the result
+    pub fn step(&mut self) -> &Step {
+        let (mixture, output) = R::react_once_with_output(self.last());
+        self.push(mixture, output)
+    }
+
+    /// Record an externally reacted mixture as the next step
+    pub fn push(&mut self, mixture: GasMixture, output: ReactionOutput) -> &Step {
+        let previous = self.last();
+        self.steps.push(Step {
+            mixture,
+            temperature: mixture.temperature,
+            pressure: mixture.get_pressure(),
+            energy: mixture.get_energy(),
+            deltas: mixture.gases - previous.gases,
+            output,
+        });
+
+        self.steps.last().unwrap()
+    }
+
+    fn mixtures(&self) -> impl Iterator<Item = &GasMixture> {
+        std::iter::once(&self.initial).chain(self.steps.iter().map(|step| &step.mixture))
+    }
+
+    pub fn peak_pressure(&self) -> f64 {
+        self.mixtures()
+            .map(|gm| gm.get_pressure())
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    pub fn peak_temperature(&self) -> f64 {
+        self.mixtures()
+            .map(|gm| gm.temperature)
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Number of the first step where any fuel burned
+    pub fn first_fire(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.output.fire > 0.)
+            .map(|index| index + 1)
+    }
+
+    /// Number of the first step where any reaction changed the mixture
+    pub fn first_reaction(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.output.fired != 0)
+            .map(|index| index + 1)
+    }
+
+    /// Thermal energy released by the steps that heated the mixture up, the ones that used up energy don't count against it
+    pub fn total_energy_released(&self) -> f64 {
+        self.mixtures()
+            .zip(self.steps.iter())
+            .map(|(previous, step)| (step.energy - previous.get_energy()).max(0.))
+            .sum()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let gases: Vec<String> = self
+            .initial
+            .gases
+            .0
+            .iter()
+            .map(|(gas, _)| format!("{:?}", gas))
+            .collect();
+
+        let mut csv = String::from("step,temperature,pressure,energy");
+        gases
+            .iter()
+            .for_each(|gas| write!(csv, ",{}", gas).unwrap());
+        gases
+            .iter()
+            .for_each(|gas| write!(csv, ",delta_{}", gas).unwrap());
+        csv.push_str(",research,radiation,fire,fired\n");
+
+        for (index, step) in self.steps.iter().enumerate() {
+            write!(
+                csv,
+                "{},{},{},{}",
+                index + 1,
+                step.temperature,
+                step.pressure,
+                step.energy
+            )
+            .unwrap();
+            step.mixture
+                .gases
+                .0
+                .values()
+                .for_each(|amount| write!(csv, ",{}", amount).unwrap());
+            step.deltas
+                .0
+                .values()
+                .for_each(|delta| write!(csv, ",{}", delta).unwrap());
+            writeln!(
+                csv,
+                ",{},{},{},{}",
+                step.output.research,
+                step.output.radiation,
+                step.output.fire,
+                fired_ids(&step.output).join(";")
+            )
+            .unwrap();
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                format!(
+                    "{{\"step\":{},\"temperature\":{},\"pressure\":{},\"energy\":{},\"gases\":{},\"deltas\":{},\"research\":{},\"radiation\":{},\"fire\":{},\"fired\":[{}]}}",
+                    index + 1,
+                    json_number(step.temperature),
+                    json_number(step.pressure),
+                    json_number(step.energy),
+                    json_gases(&step.mixture.gases),
+                    json_gases(&step.deltas),
+                    json_number(step.output.research),
+                    json_number(step.output.radiation),
+                    json_number(step.output.fire),
+                    fired_ids(&step.output)
+                        .iter()
+                        .map(|id| format!("\"{}\"", id))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"initial\":{{\"temperature\":{},\"pressure\":{},\"energy\":{},\"volume\":{},\"gases\":{}}},\"steps\":[{}],\"summary\":{{\"peak_pressure\":{},\"peak_temperature\":{},\"first_fire\":{},\"first_reaction\":{},\"total_energy_released\":{}}}}}",
+            json_number(self.initial.temperature),
+            json_number(self.initial.get_pressure()),
+            json_number(self.initial.get_energy()),
+            json_number(self.initial.volume),
+            json_gases(&self.initial.gases),
+            steps.join(","),
+            json_number(self.peak_pressure()),
+            json_number(self.peak_temperature()),
+            self.first_fire().map_or("null".to_owned(), |step| step.to_string()),
+            self.first_reaction().map_or("null".to_owned(), |step| step.to_string()),
+            json_number(self.total_energy_released())
+        )
+    }
+}
+
+fn fired_ids(output: &ReactionOutput) -> Vec<&'static str> {
+    output
+        .fired_reactions()
+        .map(|reaction| reaction.id)
+        .collect()
+}
+
+/// JSON has no infinities or NaN, those become null
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+fn json_gases(gases: &GasVec) -> String {
+    let fields: Vec<String> = gases
+        .0
+        .iter()
+        .map(|(gas, amount)| format!("\"{:?}\":{}", gas, json_number(*amount)))
+        .collect();
+
+    format!("{{{}}}", fields.join(","))
+}